@@ -12,6 +12,13 @@ fn bench_parse_files(c: &mut Criterion) {
         });
     });
 
+    c.bench_function("minimal.xdf - 4.0K - probe", |b| {
+        b.iter(|| {
+            let summary = xdf::XDFFile::probe(black_box(&minimal_bytes)).unwrap();
+            black_box(summary);
+        });
+    });
+
     // benchmarks for bigger files which can be downloaded from the link above.
     // They are much less consistent across trials than the small one, likely for scheduling and i/o reasons.
 
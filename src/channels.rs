@@ -0,0 +1,146 @@
+use xmltree::{Element, XMLNode};
+
+// the channel metadata as described by the XDF meta-data conventions:
+// <desc>
+//     <channels>
+//         <channel>
+//             <label>C3</label>
+//             <unit>microvolts</unit>
+//             <type>EEG</type>
+//             ... content type specific fields, for example <location> for EEG or <object> for Mocap
+//         </channel>
+//         ...
+//     </channels>
+// </desc>
+
+/// The metadata of a single channel of a stream, see [`crate::Stream::channels`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelInfo {
+    /// The label of the channel, for example the name of an EEG electrode like "C3".
+    /// Generated from the channel's position ("Ch1", "Ch2", ...) if the header does not give one.
+    pub label: String,
+    /// The unit of the channel's values, for example "microvolts", if given.
+    pub unit: Option<String>,
+    /// The type of the channel, for example "EEG", "EOG" or `PositionX`, if given.
+    pub r#type: Option<String>,
+    /// All other elements of the channel, for example its `location` or `hardware` for EEG
+    /// or its `marker` and `object` for motion capture.
+    pub extra: Vec<Element>,
+}
+
+impl ChannelInfo {
+    fn generated(index: usize) -> Self {
+        Self {
+            label: format!("Ch{}", index + 1),
+            unit: None,
+            r#type: None,
+            extra: Vec::new(),
+        }
+    }
+
+    fn from_element(index: usize, channel: &Element) -> Self {
+        let text = |name: &str| {
+            channel
+                .get_child(name)
+                .and_then(Element::get_text)
+                .map(|text| text.trim().to_string())
+                .filter(|text| !text.is_empty())
+        };
+
+        let extra = channel
+            .children
+            .iter()
+            .filter_map(XMLNode::as_element)
+            .filter(|e| !matches!(e.name.as_str(), "label" | "unit" | "type"))
+            .cloned()
+            .collect();
+
+        Self {
+            label: text("label").unwrap_or_else(|| Self::generated(index).label),
+            unit: text("unit"),
+            r#type: text("type"),
+            extra,
+        }
+    }
+}
+
+// the metadata of every channel described in the header, or generated metadata if the header has none.
+// Channels missing from the header are generated as well and extra ones are left out,
+// so there is exactly one per channel.
+pub(crate) fn channels(header: &Element, channel_count: u32) -> Vec<ChannelInfo> {
+    let channel_count = channel_count as usize;
+    let described = header
+        .get_child("desc")
+        .and_then(|desc| desc.get_child("channels"))
+        .into_iter()
+        .flat_map(|channels| channels.children.iter().filter_map(XMLNode::as_element))
+        .filter(|e| e.name == "channel");
+
+    let mut channels: Vec<ChannelInfo> = described
+        .take(channel_count)
+        .enumerate()
+        .map(|(i, channel)| ChannelInfo::from_element(i, channel))
+        .collect();
+
+    if channels.len() < channel_count {
+        channels.extend((channels.len()..channel_count).map(ChannelInfo::generated));
+    }
+
+    channels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channels() {
+        let header = Element::parse(
+            "<info><desc><channels>
+                <channel><label>C3</label><unit>microvolts</unit><type>EEG</type>
+                    <location><X>-1</X><Y>0</Y><Z>1</Z></location></channel>
+                <channel><label> Cz </label></channel>
+                <channel><unit>microvolts</unit></channel>
+            </channels></desc></info>"
+                .as_bytes(),
+        )
+        .unwrap();
+
+        let channels = channels(&header, 4);
+        let labels: Vec<&str> = channels.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, ["C3", "Cz", "Ch3", "Ch4"]);
+
+        assert_eq!(channels[0].unit.as_deref(), Some("microvolts"));
+        assert_eq!(channels[0].r#type.as_deref(), Some("EEG"));
+        assert_eq!(channels[0].extra.len(), 1);
+        assert_eq!(channels[0].extra[0].name, "location");
+        assert_eq!(channels[1].unit, None);
+        assert_eq!(channels[2].unit.as_deref(), Some("microvolts"));
+    }
+
+    #[test]
+    fn test_generated_channels() {
+        let header = Element::parse("<info><desc/></info>".as_bytes()).unwrap();
+        assert_eq!(
+            channels(&header, 2),
+            [ChannelInfo::generated(0), ChannelInfo::generated(1)]
+        );
+        assert_eq!(channels(&header, 2)[1].label, "Ch2");
+    }
+
+    #[test]
+    fn test_extra_channels() {
+        let header = Element::parse(
+            "<info><desc><channels>
+                <channel><label>C3</label></channel>
+                <channel><label>C4</label></channel>
+                <channel><label>Cz</label></channel>
+            </channels></desc></info>"
+                .as_bytes(),
+        )
+        .unwrap();
+
+        let labels: Vec<String> = channels(&header, 2).into_iter().map(|c| c.label).collect();
+        assert_eq!(labels, ["C3", "C4"]);
+    }
+}
@@ -0,0 +1,893 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::Read,
+    ops::Range,
+};
+
+use crate::{
+    chunk_structs::{Chunk, ChunkKind, StreamHeaderChunkInfo, BOUNDARY_UUID},
+    errors::{ChunkError, Limit, ParseError, ParseErrorReason},
+    parsers::{
+        truncated_samples,
+        xdf_file::{chunk, magic_number},
+        Error,
+    },
+    Format, ParseLimits, Sample, StreamID, Values, XDFError,
+};
+
+/// A chunk together with its position in the file, as yielded by a [`ChunkReader`].
+#[derive(Debug, Clone)]
+pub struct LocatedChunk {
+    /// The byte offset of the chunk from the start of the file, pointing at its number of length bytes.
+    pub offset: u64,
+    /// The number of bytes used to encode the length of the chunk: 1, 4 or 8.
+    pub num_length_bytes: u8,
+    /// The length of the chunk as given in the file, including the two tag bytes but not the length itself.
+    pub length: u64,
+    /// The parsed chunk.
+    pub chunk: Chunk,
+}
+
+/**
+A chunk which is cut off by the end of the input, for example because the recording stopped unexpectedly.
+
+See [`ChunkReader::truncation`] and [`crate::XDFFile::truncation`].
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Truncation {
+    /// The byte offset of the cut-off chunk from the start of the file.
+    pub offset: u64,
+    /// The number of bytes of the chunk present in the input, including its number of length bytes and its length.
+    pub available_bytes: u64,
+    /// The number of bytes missing from the end of the chunk, or `None` if the input ends within the length itself.
+    pub missing_bytes: Option<u64>,
+    /// The stream ID if the chunk is a samples chunk and its stream ID is still present.
+    pub stream_id: Option<u32>,
+    /// The number of samples the chunk should contain, if it is a samples chunk and that number is still present.
+    pub declared_samples: Option<usize>,
+    /// The number of complete samples decoded from the chunk, see [`ChunkReader::decode_truncated`].
+    pub recovered_samples: usize,
+    /// The streams whose missing footers were synthesized, see [`crate::ParseOptions::lenient`].
+    /// Always empty when reported by a [`ChunkReader`].
+    pub synthesized_footers: Vec<u32>,
+}
+
+// a reader which can be made to read some bytes again, needed to search for boundaries in chunks that failed to parse
+struct Rewind<R: Read> {
+    inner: R,
+    pending: Vec<u8>,
+    position: usize,
+}
+
+impl<R: Read> Rewind<R> {
+    // the given bytes are read again before anything else
+    fn unread(&mut self, bytes: &[u8]) {
+        let mut pending = bytes.to_vec();
+        pending.extend_from_slice(&self.pending[self.position..]);
+        self.pending = pending;
+        self.position = 0;
+    }
+}
+
+impl<R: Read> Read for Rewind<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.position < self.pending.len() {
+            let read = (&self.pending[self.position..]).read(buf)?;
+            self.position += read;
+            return Ok(read);
+        }
+
+        self.inner.read(buf)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    // the magic number has not been read yet
+    Start,
+    Chunks,
+    // the last chunk failed to parse and the next boundary has to be found first
+    Resynchronize,
+    Done,
+}
+
+/**
+Reads an XDF file one chunk at a time, yielding each [`Chunk`] together with its position in the file.
+
+Only the bytes of the current chunk are kept in memory. Unlike [`crate::XDFFile`], the chunks are not checked
+for being in a sensible order and nothing is combined into streams, so this can be used to inspect files at a low level.
+After the first error, the iterator ends, unless [`ChunkReader::recover`] is enabled.
+
+# Example
+```rust
+# use std::fs::File;
+# use std::io::BufReader;
+# use xdf::{Chunk, ChunkReader};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let file = BufReader::new(File::open("tests/minimal.xdf")?);
+for located_chunk in ChunkReader::new(file) {
+    let located_chunk = located_chunk?;
+    if let Chunk::Samples(samples_chunk) = located_chunk.chunk {
+        println!(
+            "{} samples of stream {} at byte {}",
+            samples_chunk.samples.len(),
+            samples_chunk.stream_id,
+            located_chunk.offset
+        );
+    }
+}
+# Ok(())
+# }
+```
+*/
+pub struct ChunkReader<R: Read> {
+    reader: Rewind<R>,
+    // the bytes of the current chunk, reused for every chunk
+    buffer: Vec<u8>,
+    // the info of every stream header read so far, needed to parse samples chunks
+    stream_info: HashMap<StreamID, StreamHeaderChunkInfo>,
+    // the offset of the next byte to be read from the reader
+    offset: u64,
+    // the position of the chunk currently in the buffer
+    chunk_offset: u64,
+    // the index of the chunk currently in the buffer and of the next one
+    chunk_index: u64,
+    next_chunk_index: u64,
+    num_length_bytes: u8,
+    length: u64,
+    state: State,
+    skip_samples: bool,
+    skipped_streams: HashSet<StreamID>,
+    recover: bool,
+    skipped_ranges: Vec<Range<u64>>,
+    decode_truncated: bool,
+    truncation: Option<Truncation>,
+    limits: ParseLimits,
+    // the size of all samples decoded so far, see ParseLimits::max_decoded_bytes
+    decoded_bytes: u64,
+    // the size kept for each sample in addition to the sample itself, see ParseOptions::timestamp_columns
+    extra_sample_bytes: u64,
+}
+
+impl<R: Read> ChunkReader<R> {
+    /// Create a new chunk reader. The reader must be positioned at the start of the file, before the magic number.
+    /// Consider wrapping it in a [`std::io::BufReader`], as the chunks are read in many small reads.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: Rewind {
+                inner: reader,
+                pending: Vec::new(),
+                position: 0,
+            },
+            buffer: Vec::new(),
+            stream_info: HashMap::new(),
+            offset: 0,
+            chunk_offset: 0,
+            chunk_index: 0,
+            next_chunk_index: 0,
+            num_length_bytes: 0,
+            length: 0,
+            state: State::Start,
+            skip_samples: false,
+            skipped_streams: HashSet::new(),
+            recover: false,
+            skipped_ranges: Vec::new(),
+            decode_truncated: false,
+            truncation: None,
+            limits: ParseLimits::default(),
+            decoded_bytes: 0,
+            extra_sample_bytes: 0,
+        }
+    }
+
+    /**
+    Recover from chunks that can't be parsed instead of ending the iteration.
+
+    After a chunk fails to parse, its error is yielded as usual. The reader then searches for the next boundary chunk,
+    starting right after the first byte of the broken chunk, and continues with the chunk following that boundary.
+    The bytes skipped this way are available through [`ChunkReader::skipped_ranges`].
+    The magic number and I/O errors can't be recovered from.
+    */
+    #[must_use]
+    pub const fn recover(mut self, recover: bool) -> Self {
+        self.recover = recover;
+        self
+    }
+
+    pub(crate) const fn is_recovering(&self) -> bool {
+        self.recover
+    }
+
+    /// The byte ranges skipped while recovering from broken chunks so far. Each range starts at a chunk that failed to parse
+    /// and ends after the boundary chunk where reading continued, or at the end of the input if there was none.
+    #[must_use]
+    pub fn skipped_ranges(&self) -> &[Range<u64>] {
+        &self.skipped_ranges
+    }
+
+    /**
+    Decode the complete samples of a samples chunk which is cut off by the end of the input.
+
+    Without this, a cut-off chunk fails to parse like any other broken chunk. With it, a cut-off samples chunk is yielded
+    with all samples that are complete, and the iteration ends after it. Either way, what was lost is reported by
+    [`ChunkReader::truncation`].
+    */
+    #[must_use]
+    pub const fn decode_truncated(mut self, decode: bool) -> Self {
+        self.decode_truncated = decode;
+        self
+    }
+
+    /// Limit what the input may contain, see [`ParseLimits`]. Exceeding a limit is an error like any other chunk that
+    /// fails to parse.
+    #[must_use]
+    pub const fn limits(mut self, limits: ParseLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    // count the timestamp columns kept for each sample towards ParseLimits::max_decoded_bytes
+    pub(crate) const fn count_timestamp_columns(mut self, count: bool) -> Self {
+        self.extra_sample_bytes = if count { 3 * size_of::<Option<f64>>() as u64 } else { 0 };
+        self
+    }
+
+    /// The chunk which was cut off by the end of the input, if the input ended in the middle of a chunk.
+    /// Only available once that chunk has been reached.
+    #[must_use]
+    pub const fn truncation(&self) -> Option<&Truncation> {
+        self.truncation.as_ref()
+    }
+
+    /// Skip samples chunks instead of parsing them. They are skipped using their length, without decoding a single sample,
+    /// and are not yielded at all.
+    #[must_use]
+    pub const fn skip_samples(mut self, skip: bool) -> Self {
+        self.skip_samples = skip;
+        self
+    }
+
+    /// Skip the samples chunks of a single stream from now on, like [`ChunkReader::skip_samples`] does for all streams.
+    pub fn skip_samples_of(&mut self, stream_id: u32) {
+        self.skipped_streams.insert(stream_id);
+    }
+
+    // whether a chunk starting with these bytes should be skipped
+    fn should_skip(&self, chunk_start: &[u8]) -> bool {
+        if chunk_start.first_chunk::<2>() != Some(&ChunkKind::Samples.to_le_bytes()) {
+            return false;
+        }
+        if self.skip_samples {
+            return true;
+        }
+
+        samples_stream_id(chunk_start).is_some_and(|stream_id| self.skipped_streams.contains(&stream_id))
+    }
+
+    /// The byte offset of the next chunk from the start of the file.
+    #[must_use]
+    pub const fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    // skips up to `len` bytes without keeping them. Returns fewer bytes only at the end of the input.
+    fn skip(&mut self, len: u64) -> Result<u64, XDFError> {
+        let skipped = std::io::copy(&mut (&mut self.reader).take(len), &mut std::io::sink())?;
+        self.offset += skipped;
+        Ok(skipped)
+    }
+
+    // appends up to `len` bytes to the buffer. Returns fewer bytes only at the end of the input.
+    fn read_into_buffer(&mut self, len: u64) -> Result<usize, XDFError> {
+        // reading through take avoids allocating the whole length up front, which could be a lie
+        let read = (&mut self.reader).take(len).read_to_end(&mut self.buffer)?;
+        self.offset += read as u64;
+        Ok(read)
+    }
+
+    fn read_magic_number(&mut self) -> Result<(), XDFError> {
+        self.buffer.clear();
+        self.read_into_buffer(4)?;
+        magic_number(&self.buffer).map_err(|_| ParseError::NoMagicNumber)?;
+        Ok(())
+    }
+
+    // reads the bytes of the next chunk into the buffer. Returns false if there are no more chunks.
+    // A chunk that is cut off by the end of the input is read as far as possible and left for the parser to reject.
+    fn read_chunk_bytes(&mut self) -> Result<bool, XDFError> {
+        loop {
+            self.buffer.clear();
+            self.chunk_offset = self.offset;
+            self.chunk_index = self.next_chunk_index;
+            self.num_length_bytes = 0;
+            self.length = 0;
+
+            if self.read_into_buffer(1)? == 0 {
+                return Ok(false);
+            }
+            self.next_chunk_index += 1;
+
+            let num_length_bytes = self.buffer[0];
+            self.num_length_bytes = num_length_bytes;
+            if !matches!(num_length_bytes, 1 | 4 | 8) {
+                // invalid, but the parser will produce the fitting error
+                return Ok(true);
+            }
+
+            let read = self.read_into_buffer(u64::from(num_length_bytes))?;
+            if read < usize::from(num_length_bytes) {
+                return Ok(true);
+            }
+
+            let mut length_bytes = [0_u8; 8];
+            length_bytes[..read].copy_from_slice(&self.buffer[1..]);
+            let length = u64::from_le_bytes(length_bytes);
+            self.length = length;
+            if length > self.limits.max_chunk_size {
+                let reason = ParseErrorReason::LimitExceeded {
+                    limit: Limit::ChunkSize,
+                    value: length,
+                    max: self.limits.max_chunk_size,
+                };
+                return Err(self.chunk_error_at(1, reason));
+            }
+
+            if !self.skip_samples && self.skipped_streams.is_empty() {
+                self.read_into_buffer(length)?;
+                return Ok(true);
+            }
+
+            // read the tag and stream id first to decide whether to skip the rest
+            let read = self.read_into_buffer(length.min(6))?;
+            let content_start = self.buffer.len() - read;
+            if !self.should_skip(&self.buffer[content_start..]) {
+                self.read_into_buffer(length - read as u64)?;
+                return Ok(true);
+            }
+
+            let remaining = length - read as u64;
+            let skipped = self.skip(remaining)?;
+            if skipped < remaining {
+                self.truncation = Some(Truncation {
+                    offset: self.chunk_offset,
+                    available_bytes: self.buffer.len() as u64 + skipped,
+                    missing_bytes: Some(remaining - skipped),
+                    stream_id: samples_stream_id(&self.buffer[content_start..]),
+                    declared_samples: None,
+                    recovered_samples: 0,
+                    synthesized_footers: Vec::new(),
+                });
+            }
+        }
+    }
+
+    // searches for the next boundary after the start of the chunk that failed to parse.
+    // Reading continues right after the boundary.
+    fn resynchronize(&mut self) -> Result<(), XDFError> {
+        const BLOCK_SIZE: usize = 8192;
+
+        let start = self.chunk_offset;
+        // the failed chunk could contain the boundary, so it is searched as well
+        if let Some(rest) = self.buffer.get(1..) {
+            self.reader.unread(rest);
+            self.offset -= rest.len() as u64;
+        }
+
+        // searched bytes, keeping the end of the previous block in case the boundary is split between two blocks
+        let mut window: Vec<u8> = Vec::new();
+        let mut block = [0_u8; BLOCK_SIZE];
+        loop {
+            let read = match self.reader.read(&mut block) {
+                Ok(read) => read,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            if read == 0 {
+                self.skipped_ranges.push(start..self.offset);
+                return Ok(());
+            }
+            self.offset += read as u64;
+            window.extend_from_slice(&block[..read]);
+
+            if let Some(position) = window.windows(BOUNDARY_UUID.len()).position(|w| w == BOUNDARY_UUID) {
+                let after_boundary = position + BOUNDARY_UUID.len();
+                self.reader.unread(&window[after_boundary..]);
+                self.offset -= (window.len() - after_boundary) as u64;
+                self.skipped_ranges.push(start..self.offset);
+                return Ok(());
+            }
+
+            window.drain(..window.len().saturating_sub(BOUNDARY_UUID.len() - 1));
+        }
+    }
+
+    fn try_next_chunk(&mut self) -> Result<Option<LocatedChunk>, XDFError> {
+        match self.state {
+            State::Start => self.read_magic_number()?,
+            State::Resynchronize => self.resynchronize()?,
+            State::Chunks | State::Done => (),
+        }
+        self.state = State::Chunks;
+
+        if !self.read_chunk_bytes()? {
+            return Ok(None);
+        }
+
+        self.check_declared_decoded_bytes()?;
+
+        let chunk = match chunk(&self.buffer, &self.stream_info, &self.limits) {
+            Ok((_rest, chunk)) => chunk,
+            Err(e) => {
+                let error = self.chunk_error(&e);
+                self.truncated_chunk().ok_or(error)?
+            }
+        };
+
+        if let Chunk::Samples(samples_chunk) = &chunk {
+            self.decoded_bytes += samples_chunk
+                .samples
+                .iter()
+                .map(|sample| decoded_size(sample) + self.extra_sample_bytes)
+                .sum::<u64>();
+            if self.decoded_bytes > self.limits.max_decoded_bytes {
+                let position = 1 + usize::from(self.num_length_bytes);
+                let reason = ParseErrorReason::LimitExceeded {
+                    limit: Limit::DecodedBytes,
+                    value: self.decoded_bytes,
+                    max: self.limits.max_decoded_bytes,
+                };
+                return Err(self.chunk_error_at(position, reason));
+            }
+        }
+
+        if let Chunk::StreamHeader(stream_header) = &chunk {
+            self.stream_info
+                .insert(stream_header.stream_id, stream_header.info.clone());
+        }
+
+        Ok(Some(LocatedChunk {
+            offset: self.chunk_offset,
+            num_length_bytes: self.num_length_bytes,
+            length: self.length,
+            chunk,
+        }))
+    }
+
+    // checks ParseLimits::max_decoded_bytes before decoding a samples chunk in the buffer, using the size its samples take
+    // up at least according to the number of samples and channels. The size of strings is only known after decoding.
+    fn check_declared_decoded_bytes(&self) -> Result<(), XDFError> {
+        let tag_start = 1 + usize::from(self.num_length_bytes);
+        let Some((stream_id, num_samples, content)) = self.buffer.get(tag_start..).and_then(samples_header) else {
+            return Ok(());
+        };
+        let Some(stream_info) = self.stream_info.get(&stream_id) else {
+            return Ok(());
+        };
+
+        let value_size = value_size(stream_info.channel_format);
+        // a lying number of samples can not make up more samples than there are bytes for
+        let encoded_sample_size = 1 + u64::from(stream_info.channel_count) * value_size.max(1);
+        let num_samples = num_samples.min(content.len() as u64 / encoded_sample_size);
+        let sample_size =
+            size_of::<Sample>() as u64 + u64::from(stream_info.channel_count) * value_size + self.extra_sample_bytes;
+
+        let decoded_bytes = self
+            .decoded_bytes
+            .saturating_add(num_samples.saturating_mul(sample_size));
+        if decoded_bytes > self.limits.max_decoded_bytes {
+            let reason = ParseErrorReason::LimitExceeded {
+                limit: Limit::DecodedBytes,
+                value: decoded_bytes,
+                max: self.limits.max_decoded_bytes,
+            };
+            return Err(self.chunk_error_at(tag_start, reason));
+        }
+        Ok(())
+    }
+
+    // describes where and why the chunk in the buffer failed to parse
+    fn chunk_error(&self, e: &nom::Err<Error<&[u8]>>) -> XDFError {
+        let (position, reason) = match e {
+            nom::Err::Error(e) | nom::Err::Failure(e) => (self.position_in_buffer(e.input), e.reason.clone()),
+            nom::Err::Incomplete(_) => (self.buffer.len(), ParseErrorReason::UnexpectedEnd),
+        };
+        self.chunk_error_at(position, reason)
+    }
+
+    // an error for the chunk in the buffer at the given position within it
+    fn chunk_error_at(&self, position: usize, reason: ParseErrorReason) -> XDFError {
+        let position = position.min(self.buffer.len());
+
+        let tag_start = 1 + usize::from(self.num_length_bytes);
+        let kind = self
+            .buffer
+            .get(tag_start..)
+            .and_then(<[u8]>::first_chunk::<2>)
+            .and_then(|tag| ChunkKind::from_tag(u16::from_le_bytes(*tag)));
+
+        let excerpt_end = self.buffer.len().min(position + ChunkError::EXCERPT_LEN);
+        let chunk_error = ChunkError {
+            offset: self.chunk_offset + position as u64,
+            chunk_offset: self.chunk_offset,
+            chunk_index: self.chunk_index,
+            kind,
+            reason,
+            excerpt: self.buffer[position..excerpt_end].to_vec(),
+        };
+        ParseError::from(chunk_error).into()
+    }
+
+    // where a slice of the buffer starts within it
+    fn position_in_buffer(&self, slice: &[u8]) -> usize {
+        slice
+            .as_ptr()
+            .addr()
+            .checked_sub(self.buffer.as_ptr().addr())
+            .filter(|&position| position <= self.buffer.len())
+            .unwrap_or(0)
+    }
+
+    // records what was lost if the chunk in the buffer is cut off by the end of the input.
+    // Returns the complete samples of a cut-off samples chunk if they should be decoded.
+    fn truncated_chunk(&mut self) -> Option<Chunk> {
+        if !matches!(self.num_length_bytes, 1 | 4 | 8) {
+            return None;
+        }
+
+        let available_bytes = self.buffer.len() as u64;
+        let prefix_len = 1 + u64::from(self.num_length_bytes);
+        let missing_bytes = if available_bytes < prefix_len {
+            None
+        } else {
+            let chunk_len = prefix_len.saturating_add(self.length);
+            if available_bytes >= chunk_len {
+                return None;
+            }
+            Some(chunk_len - available_bytes)
+        };
+
+        let content = self
+            .buffer
+            .get(1 + usize::from(self.num_length_bytes)..)
+            .unwrap_or_default();
+        let decoded = truncated_samples(content, &self.stream_info, &self.limits)
+            .ok()
+            .map(|(_, decoded)| decoded);
+
+        let mut truncation = Truncation {
+            offset: self.chunk_offset,
+            available_bytes,
+            missing_bytes,
+            stream_id: samples_stream_id(content),
+            declared_samples: decoded.as_ref().map(|(_, declared)| *declared),
+            recovered_samples: 0,
+            synthesized_footers: Vec::new(),
+        };
+
+        let truncated_chunk = match decoded {
+            Some((samples_chunk, _)) if self.decode_truncated => {
+                truncation.recovered_samples = samples_chunk.samples.len();
+                Some(Chunk::Samples(samples_chunk))
+            }
+            _ => None,
+        };
+        self.truncation = Some(truncation);
+
+        truncated_chunk
+    }
+}
+
+// the size of a decoded sample in memory, see ParseLimits::max_decoded_bytes
+fn decoded_size(sample: &Sample) -> u64 {
+    let values_size = match &sample.values {
+        Values::Int8(v) => size_of_val(v.as_slice()),
+        Values::Int16(v) => size_of_val(v.as_slice()),
+        Values::Int32(v) => size_of_val(v.as_slice()),
+        Values::Int64(v) => size_of_val(v.as_slice()),
+        Values::Float32(v) => size_of_val(v.as_slice()),
+        Values::Float64(v) => size_of_val(v.as_slice()),
+        Values::String(s) => s.len(),
+    };
+    (size_of::<Sample>() + values_size) as u64
+}
+
+// the size of a single decoded value of the format, strings are counted after decoding
+const fn value_size(format: Format) -> u64 {
+    match format {
+        Format::Int8 => 1,
+        Format::Int16 => 2,
+        Format::Int32 | Format::Float32 => 4,
+        Format::Int64 | Format::Float64 => 8,
+        Format::String => 0,
+    }
+}
+
+// the stream ID, the number of samples and the encoded samples of a chunk starting with these bytes,
+// if it is a samples chunk
+fn samples_header(chunk_start: &[u8]) -> Option<(u32, u64, &[u8])> {
+    let stream_id = samples_stream_id(chunk_start)?;
+    let (&num_length_bytes, rest) = chunk_start.get(6..)?.split_first()?;
+    if !matches!(num_length_bytes, 1 | 4 | 8) {
+        return None;
+    }
+
+    let (length_bytes, content) = rest.split_at_checked(usize::from(num_length_bytes))?;
+    let mut num_samples = [0_u8; 8];
+    num_samples[..length_bytes.len()].copy_from_slice(length_bytes);
+    Some((stream_id, u64::from_le_bytes(num_samples), content))
+}
+
+// the stream ID of a chunk starting with these bytes, if it is a samples chunk
+fn samples_stream_id(chunk_start: &[u8]) -> Option<u32> {
+    let (tag, rest) = chunk_start.split_first_chunk::<2>()?;
+    if *tag != ChunkKind::Samples.to_le_bytes() {
+        return None;
+    }
+
+    rest.first_chunk::<4>().map(|stream_id| u32::from_le_bytes(*stream_id))
+}
+
+impl<R: Read> Iterator for ChunkReader<R> {
+    type Item = Result<LocatedChunk, XDFError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.state == State::Done {
+            return None;
+        }
+
+        let result = self.try_next_chunk().transpose();
+        match &result {
+            Some(Ok(_)) => (),
+            // only chunks that failed to parse can be recovered from, after the magic number has been read
+            Some(Err(XDFError::Parse(_))) if self.recover && self.state == State::Chunks => {
+                self.state = State::Resynchronize;
+            }
+            _ => self.state = State::Done,
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writers;
+
+    #[test]
+    fn test_chunk_positions() {
+        let input: &[u8] = include_bytes!("../tests/minimal.xdf");
+
+        let positions: Vec<(u64, u8, u64)> = ChunkReader::new(input)
+            .map(|c| c.map(|c| (c.offset, c.num_length_bytes, c.length)))
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(positions.len(), 15);
+        assert_eq!(positions[0], (4, 1, 58)); // file header, right after the magic number
+        assert_eq!(positions[1], (64, 4, 258)); // first stream header
+        assert_eq!(positions[3], (605, 1, 18)); // boundary
+        assert_eq!(positions[14], (1618, 4, 327)); // last stream footer
+
+        // every chunk starts where the previous one ended
+        for (previous, next) in positions.iter().zip(&positions[1..]) {
+            assert_eq!(previous.0 + 1 + u64::from(previous.1) + previous.2, next.0);
+        }
+    }
+
+    #[test]
+    fn test_skip_samples() {
+        let input: &[u8] = include_bytes!("../tests/minimal.xdf");
+
+        let chunks: Vec<LocatedChunk> = ChunkReader::new(input)
+            .skip_samples(true)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        // the six samples chunks are gone, the offsets of the others are unchanged
+        assert_eq!(chunks.len(), 9);
+        assert!(!chunks.iter().any(|c| matches!(c.chunk, Chunk::Samples(_))));
+        assert_eq!(chunks[4].offset, 1218);
+    }
+
+    #[test]
+    fn test_recover_inside_broken_chunk() {
+        // turn the first samples chunk's length into a 4 byte one, so that it swallows the next boundary
+        let mut input = include_bytes!("../tests/minimal.xdf").to_vec();
+        input[625] = 4;
+
+        let mut reader = ChunkReader::new(input.as_slice()).recover(true);
+        let results: Vec<_> = reader.by_ref().collect();
+
+        assert_eq!(results.iter().filter(|r| r.is_err()).count(), 1);
+        assert_eq!(reader.skipped_ranges(), [Range { start: 625, end: 1238 }]);
+
+        // the clock offsets and footers after the boundary are still read
+        let offsets: Vec<u64> = results.into_iter().filter_map(Result::ok).map(|c| c.offset).collect();
+        assert_eq!(offsets, [4, 64, 327, 605, 1238, 1262, 1286, 1618]);
+    }
+
+    #[test]
+    fn test_truncated_samples_chunk() {
+        // cut the file in the middle of the fifth samples chunk, which has four samples
+        let input = &include_bytes!("../tests/minimal.xdf")[..1100];
+
+        let mut reader = ChunkReader::new(input);
+        assert!(reader.by_ref().last().unwrap().is_err());
+        assert_eq!(reader.truncation().unwrap().recovered_samples, 0);
+
+        let mut reader = ChunkReader::new(input).decode_truncated(true);
+        let last = reader.by_ref().last().unwrap().unwrap();
+        let Chunk::Samples(samples_chunk) = last.chunk else {
+            panic!("expected a samples chunk");
+        };
+        assert_eq!(samples_chunk.samples.len(), 2);
+
+        let truncation = reader.truncation().unwrap();
+        assert_eq!(truncation.offset, 1061);
+        assert_eq!(truncation.available_bytes, 39);
+        assert_eq!(truncation.missing_bytes, Some(19));
+        assert_eq!(truncation.stream_id, Some(0x02C0_FFEE));
+        assert_eq!(truncation.declared_samples, Some(4));
+        assert_eq!(truncation.recovered_samples, 2);
+    }
+
+    #[test]
+    fn test_error_location() {
+        // give the first samples chunk a stream ID without a stream header
+        let mut input = include_bytes!("../tests/minimal.xdf").to_vec();
+        input[629] = 7;
+
+        let Some(Err(XDFError::Parse(ParseError::Chunk(e)))) = ChunkReader::new(input.as_slice()).nth(4) else {
+            panic!("expected the fifth chunk to fail");
+        };
+        assert_eq!(e.offset, 629);
+        assert_eq!(e.chunk_offset, 625);
+        assert_eq!(e.chunk_index, 4);
+        assert_eq!(e.kind, Some(ChunkKind::Samples));
+        assert_eq!(e.reason, ParseErrorReason::UnknownStream(7));
+        assert_eq!(e.excerpt, input[629..645]);
+        assert_eq!(
+            e.to_string(),
+            "Could not parse samples chunk #4 at byte offset 629: samples for unknown stream 7"
+        );
+    }
+
+    #[test]
+    fn test_unsupported_format() {
+        let header = xmltree::Element::parse(
+            "<info><channel_count>1</channel_count><nominal_srate>0</nominal_srate><channel_format>float128</channel_format></info>"
+                .as_bytes(),
+        )
+        .unwrap();
+        let mut input = b"XDF:".to_vec();
+        writers::file_header(
+            &mut input,
+            &xmltree::Element::parse("<info><version>1.0</version></info>".as_bytes()).unwrap(),
+        )
+        .unwrap();
+        let stream_header_offset = input.len() as u64;
+        writers::stream_header(&mut input, 1, &header).unwrap();
+
+        let Some(Err(XDFError::Parse(ParseError::Chunk(e)))) = ChunkReader::new(input.as_slice()).nth(1) else {
+            panic!("expected the stream header to fail");
+        };
+        assert_eq!(e.chunk_offset, stream_header_offset);
+        assert_eq!(e.chunk_index, 1);
+        assert_eq!(e.kind, Some(ChunkKind::StreamHeader));
+        assert_eq!(e.reason, ParseErrorReason::UnsupportedFormat("float128".to_string()));
+        assert!(e.to_string().ends_with("channel_format 'float128' unsupported"));
+    }
+
+    #[test]
+    fn test_limits() {
+        let input: &[u8] = include_bytes!("../tests/minimal.xdf");
+        let unlimited = ParseLimits::default();
+        let cases = [
+            (
+                ParseLimits {
+                    max_chunk_size: 300,
+                    ..unlimited
+                },
+                Limit::ChunkSize,
+                653,
+            ),
+            (
+                ParseLimits {
+                    max_samples_per_chunk: 0,
+                    ..unlimited
+                },
+                Limit::SamplesPerChunk,
+                625,
+            ),
+            (
+                ParseLimits {
+                    max_channels: 2,
+                    ..unlimited
+                },
+                Limit::Channels,
+                64,
+            ),
+            (
+                ParseLimits {
+                    max_string_length: 1,
+                    ..unlimited
+                },
+                Limit::StringLength,
+                653,
+            ),
+            (
+                ParseLimits {
+                    max_decoded_bytes: 100,
+                    ..unlimited
+                },
+                Limit::DecodedBytes,
+                653,
+            ),
+        ];
+
+        for (limits, expected_limit, expected_chunk_offset) in cases {
+            let error = ChunkReader::new(input).limits(limits).find_map(Result::err);
+            let Some(XDFError::Parse(ParseError::Chunk(e))) = error else {
+                panic!("expected {expected_limit:?} to be exceeded");
+            };
+            assert!(
+                matches!(e.reason, ParseErrorReason::LimitExceeded { limit, .. } if limit == expected_limit),
+                "{e}"
+            );
+            assert_eq!(e.chunk_offset, expected_chunk_offset, "{e}");
+        }
+    }
+
+    #[test]
+    fn test_decoded_bytes_checked_before_decoding() {
+        // an invalid timestamp in the first sample of the first samples chunk would fail decoding
+        let mut input = include_bytes!("../tests/minimal.xdf").to_vec();
+        input[635] = 3;
+
+        let limits = ParseLimits {
+            max_decoded_bytes: 1,
+            ..ParseLimits::default()
+        };
+        let error = ChunkReader::new(input.as_slice()).limits(limits).find_map(Result::err);
+        let Some(XDFError::Parse(ParseError::Chunk(e))) = error else {
+            panic!("expected the decoded bytes limit to be exceeded");
+        };
+        assert_eq!(e.chunk_offset, 625);
+        assert!(matches!(
+            e.reason,
+            ParseErrorReason::LimitExceeded {
+                limit: Limit::DecodedBytes,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_decoded_bytes_count_timestamp_columns() {
+        let input: &[u8] = include_bytes!("../tests/minimal.xdf");
+
+        let mut without_columns = ChunkReader::new(input);
+        let num_samples: usize = without_columns
+            .by_ref()
+            .filter_map(|c| match c.unwrap().chunk {
+                Chunk::Samples(samples_chunk) => Some(samples_chunk.samples.len()),
+                _ => None,
+            })
+            .sum();
+
+        let mut with_columns = ChunkReader::new(input).count_timestamp_columns(true);
+        with_columns.by_ref().for_each(drop);
+
+        let columns_size = num_samples as u64 * 3 * size_of::<Option<f64>>() as u64;
+        assert_eq!(with_columns.decoded_bytes, without_columns.decoded_bytes + columns_size);
+    }
+
+    #[test]
+    fn test_stops_after_error() {
+        let input: &[u8] = b"XDF:\x02";
+
+        let mut reader = ChunkReader::new(input);
+        assert!(matches!(reader.next(), Some(Err(_))));
+        assert!(reader.next().is_none());
+    }
+}
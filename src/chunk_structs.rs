@@ -1,23 +1,60 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, fmt};
 
 use xmltree::Element;
 
 use crate::{Format, Sample};
 
-#[derive(Debug)]
-pub(crate) enum Chunk {
+/// A single chunk of an XDF file, as read by a [`crate::ChunkReader`].
+#[derive(Debug, Clone)]
+pub enum Chunk {
+    /// The file header, with tag 1.
     FileHeader(FileHeaderChunk),
+    /// A stream header, with tag 2.
     StreamHeader(StreamHeaderChunk),
+    /// Samples of a stream, with tag 3.
     Samples(SamplesChunk),
+    /// A clock offset measurement of a stream, with tag 4.
     ClockOffset(ClockOffsetChunk),
+    /// A boundary chunk, with tag 5.
     Boundary(BoundaryChunk),
+    /// A stream footer, with tag 6.
     StreamFooter(StreamFooterChunk),
+    /// A chunk with a tag not defined by the specification, for example from a later version of it or a vendor extension.
+    Unknown(UnknownChunk),
 }
 
-#[derive(Debug)]
-#[doc = "The FileHeaderChunk is the first chunk in an XDF file. It contains the version of the XDF file format and an XML element that contains additional information about the file."]
-#[doc = "There must be exactly one FileHeaderChunk in an XDF file."]
-pub(crate) struct FileHeaderChunk {
+impl Chunk {
+    /// The kind of this chunk, or None if its tag is unknown.
+    #[must_use]
+    pub const fn kind(&self) -> Option<ChunkKind> {
+        match self {
+            Self::FileHeader(_) => Some(ChunkKind::FileHeader),
+            Self::StreamHeader(_) => Some(ChunkKind::StreamHeader),
+            Self::Samples(_) => Some(ChunkKind::Samples),
+            Self::ClockOffset(_) => Some(ChunkKind::ClockOffset),
+            Self::Boundary(_) => Some(ChunkKind::Boundary),
+            Self::StreamFooter(_) => Some(ChunkKind::StreamFooter),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// The ID of the stream this chunk belongs to, or None for file headers, boundaries and chunks with an unknown tag.
+    #[must_use]
+    pub const fn stream_id(&self) -> Option<u32> {
+        match self {
+            Self::StreamHeader(c) => Some(c.stream_id),
+            Self::Samples(c) => Some(c.stream_id),
+            Self::ClockOffset(c) => Some(c.stream_id),
+            Self::StreamFooter(c) => Some(c.stream_id),
+            Self::FileHeader(_) | Self::Boundary(_) | Self::Unknown(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[doc = "The `FileHeaderChunk` is the first chunk in an XDF file. It contains the version of the XDF file format and an XML element that contains additional information about the file."]
+#[doc = "There must be exactly one `FileHeaderChunk` in an XDF file."]
+pub struct FileHeaderChunk {
     /// The version of the XDF file format. Currently, only version 1.0 is supported.
     pub version: f32,
     /// The root of an XML element that contains additional information about the file.
@@ -34,34 +71,53 @@ pub(crate) struct FileHeaderChunk {
 // type
 // desc
 
+/// The fields of a stream header that are needed to read its samples, plus its name and type.
 #[derive(Debug, Clone)]
-pub(crate) struct StreamHeaderChunkInfo {
+pub struct StreamHeaderChunkInfo {
+    /// The number of channels, i.e. the number of values per sample.
     pub channel_count: u32,
+    /// The nominal sampling rate in Hz as given in the header.
     pub nominal_srate: Option<f64>,
+    /// The format of the values in this stream.
     pub channel_format: Format,
 
+    /// The name of the stream, if given.
     pub name: Option<String>,
+    /// The type of the stream, if given.
     pub stream_type: Option<String>,
+    /// The unique identifier of the device or source the stream comes from, if given.
+    pub source_id: Option<String>,
 }
 
-#[derive(Debug)]
-pub(crate) struct StreamHeaderChunk {
+/// The header of a stream. There must be exactly one per stream and it must come before any samples of that stream.
+#[derive(Debug, Clone)]
+pub struct StreamHeaderChunk {
+    /// The ID of the stream this header belongs to.
     pub stream_id: u32,
+    /// The fields parsed from the XML.
     pub info: StreamHeaderChunkInfo,
+    /// The whole XML header.
     pub xml: Element,
 }
 
-#[derive(Debug)]
-pub(crate) struct SamplesChunk {
+/// A number of samples of a single stream.
+#[derive(Debug, Clone)]
+pub struct SamplesChunk {
+    /// The ID of the stream these samples belong to.
     pub stream_id: u32,
+    /// The samples as recorded, with timestamps that are neither interpolated nor corrected.
     pub samples: Vec<Sample>,
 }
 
+/// A single clock offset measurement of a stream.
 //collection_time and offset_value are in seconds
-#[derive(Debug, PartialEq)]
-pub(crate) struct ClockOffsetChunk {
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClockOffsetChunk {
+    /// The ID of the stream this offset belongs to.
     pub stream_id: u32,
+    /// The time at which the offset was measured, in seconds.
     pub collection_time: f64,
+    /// The offset to add to the stream's timestamps, in seconds.
     pub offset_value: f64,
 }
 
@@ -73,21 +129,108 @@ impl PartialOrd for ClockOffsetChunk {
     }
 }
 
-#[derive(Debug)]
-pub(crate) struct BoundaryChunk {}
+// the 16 bytes every boundary chunk consists of
+pub(crate) const BOUNDARY_UUID: [u8; 16] = [
+    0x43, 0xA5, 0x46, 0xDC, 0xCB, 0xF5, 0x41, 0x0F, 0xB3, 0x0E, 0xD5, 0x46, 0x73, 0x83, 0xCB, 0xE4,
+];
+
+/// A boundary chunk, which only exists to allow finding the next chunk in a corrupted file.
+#[derive(Debug, Clone)]
+pub struct BoundaryChunk {}
+
+/// A chunk whose tag is not defined by the specification. It is skipped by its length and kept as it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownChunk {
+    /// The tag of the chunk.
+    pub tag: u16,
+    /// The content of the chunk after the tag.
+    pub content: Vec<u8>,
+}
 
-#[derive(Debug)]
-pub(crate) struct StreamFooterChunk {
+/// The fields of a stream footer as written by `LabRecorder`. Fields which are missing or invalid are None or empty.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StreamFooterChunkInfo {
+    /// The timestamp of the first sample of the stream, before clock correction.
+    pub first_timestamp: Option<f64>,
+    /// The timestamp of the last sample of the stream, before clock correction.
+    pub last_timestamp: Option<f64>,
+    /// The number of samples in the stream.
+    pub sample_count: Option<u64>,
+    /// The clock offsets of the stream as pairs of collection time and offset value, both in seconds.
+    pub clock_offsets: Vec<(f64, f64)>,
+}
+
+/// The footer of a stream.
+#[derive(Debug, Clone)]
+pub struct StreamFooterChunk {
+    /// The ID of the stream this footer belongs to.
     pub stream_id: u32,
+    /// The fields parsed from the XML.
+    pub info: StreamFooterChunkInfo,
+    /// The whole XML footer.
     pub xml: Element,
 }
 
-#[derive(Debug, Clone, Copy)]
-pub(crate) enum Tag {
+/// The type of a chunk, as given by the tag at its start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChunkKind {
+    /// The file header, with tag 1.
     FileHeader,
+    /// A stream header, with tag 2.
     StreamHeader,
+    /// Samples of a stream, with tag 3.
     Samples,
+    /// A clock offset measurement of a stream, with tag 4.
     ClockOffset,
+    /// A boundary chunk, with tag 5.
     Boundary,
+    /// A stream footer, with tag 6.
     StreamFooter,
 }
+
+impl ChunkKind {
+    /// The kind of chunk with the given tag, or None if the tag is not defined by the specification.
+    #[must_use]
+    pub const fn from_tag(tag: u16) -> Option<Self> {
+        match tag {
+            1 => Some(Self::FileHeader),
+            2 => Some(Self::StreamHeader),
+            3 => Some(Self::Samples),
+            4 => Some(Self::ClockOffset),
+            5 => Some(Self::Boundary),
+            6 => Some(Self::StreamFooter),
+            _ => None,
+        }
+    }
+
+    /// The tag of this kind of chunk.
+    #[must_use]
+    pub const fn tag(self) -> u16 {
+        match self {
+            Self::FileHeader => 1,
+            Self::StreamHeader => 2,
+            Self::Samples => 3,
+            Self::ClockOffset => 4,
+            Self::Boundary => 5,
+            Self::StreamFooter => 6,
+        }
+    }
+
+    // the tag as it is written to a file, in little endian
+    pub(crate) const fn to_le_bytes(self) -> [u8; 2] {
+        self.tag().to_le_bytes()
+    }
+}
+
+impl fmt::Display for ChunkKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FileHeader => write!(f, "file header"),
+            Self::StreamHeader => write!(f, "stream header"),
+            Self::Samples => write!(f, "samples"),
+            Self::ClockOffset => write!(f, "clock offset"),
+            Self::Boundary => write!(f, "boundary"),
+            Self::StreamFooter => write!(f, "stream footer"),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::fmt;
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Something was done to the data which is worth knowing about, but nothing is wrong with it.
+    Info,
+    /// The file is unusual or slightly broken, but no data was lost.
+    Warning,
+    /// Data was lost, for example because a chunk could not be parsed.
+    Error,
+}
+
+/// The kind of problem a [`Diagnostic`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticKind {
+    /// A stream has a header but no footer, for example because the recording stopped unexpectedly.
+    MissingFooter,
+    /// A stream footer has no matching stream header. The footer is ignored.
+    FooterWithoutHeader,
+    /// A footer was synthesized for a stream without one, see [`crate::ParseOptions::lenient`].
+    SynthesizedFooter,
+    /// A clock offset whose collection time or value is not finite was found, and dropped unless
+    /// [`crate::ParseOptions::drop_non_finite_clock_offsets`] is disabled.
+    NonFiniteClockOffset,
+    /// The sample count in a stream footer differs from the number of samples in the file.
+    FooterSampleCountMismatch,
+    /// The first or last timestamp in a stream footer differs from the timestamps of the samples in the file
+    /// by more than one sample interval.
+    FooterTimestampMismatch,
+    /// A stream footer lists clock offsets which are not in the file as clock offset chunks.
+    FooterClockOffsetMissing,
+    /// The file has an XDF version other than 1.x and was loaded anyway, see [`crate::ParseOptions::allow_unknown_versions`].
+    UnsupportedVersion,
+    /// The file contains more than one file header chunk. Only the first one is used,
+    /// see [`crate::ParseOptions::recover`].
+    MultipleFileHeader,
+    /// A chunk has a tag which is not defined by the specification. It was skipped and is kept in
+    /// [`crate::XDFFile::unknown_chunks`].
+    UnknownChunk,
+    /// A chunk is cut off by the end of the file, see [`crate::XDFFile::truncation`].
+    TruncatedChunk,
+    /// A chunk could not be parsed, so it and the rest of the file were ignored.
+    UnparsedBytes,
+    /// A chunk could not be parsed and was skipped while recovering, see [`crate::XDFFile::skipped_ranges`].
+    SkippedBytes,
+}
+
+/**
+A problem found while loading an XDF file which did not stop it from being loaded.
+
+The diagnostics are collected in [`crate::XDFFile::diagnostics`] in the order they were found, so that they can be
+checked programmatically, for example to flag broken recordings.
+
+# Example
+```rust
+# use std::fs;
+# use xdf::{Severity, XDFFile};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let bytes = fs::read("tests/minimal.xdf")?;
+let xdf_file = XDFFile::from_bytes(&bytes)?;
+for diagnostic in xdf_file.diagnostics.iter().filter(|d| d.severity >= Severity::Warning) {
+    println!("{diagnostic}");
+}
+# Ok(())
+# }
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// What the problem is.
+    pub kind: DiagnosticKind,
+    /// The ID of the stream the problem concerns, if any.
+    pub stream_id: Option<u32>,
+    /// The byte offset from the start of the file of the chunk the problem was found in, if any.
+    pub offset: Option<u64>,
+}
+
+impl Diagnostic {
+    pub(crate) const fn new(kind: DiagnosticKind, stream_id: Option<u32>, offset: Option<u64>) -> Self {
+        Self {
+            severity: kind.severity(),
+            kind,
+            stream_id,
+            offset,
+        }
+    }
+}
+
+impl DiagnosticKind {
+    const fn severity(self) -> Severity {
+        match self {
+            Self::SynthesizedFooter => Severity::Info,
+            Self::MissingFooter
+            | Self::FooterWithoutHeader
+            | Self::NonFiniteClockOffset
+            | Self::FooterSampleCountMismatch
+            | Self::FooterTimestampMismatch
+            | Self::FooterClockOffsetMissing
+            | Self::UnsupportedVersion
+            | Self::MultipleFileHeader
+            | Self::UnknownChunk => Severity::Warning,
+            Self::TruncatedChunk | Self::UnparsedBytes | Self::SkippedBytes => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Info => write!(f, "info"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingFooter => write!(f, "stream header without corresponding stream footer"),
+            Self::FooterWithoutHeader => write!(f, "stream footer without corresponding stream header"),
+            Self::SynthesizedFooter => write!(f, "stream footer synthesized from the samples and clock offsets"),
+            Self::NonFiniteClockOffset => write!(f, "clock offset which is not finite"),
+            Self::FooterSampleCountMismatch => {
+                write!(f, "sample count in the stream footer does not match the samples")
+            }
+            Self::FooterTimestampMismatch => write!(f, "timestamps in the stream footer do not match the samples"),
+            Self::FooterClockOffsetMissing => write!(f, "clock offsets in the stream footer are missing from the file"),
+            Self::UnsupportedVersion => write!(f, "unsupported XDF version, read like version 1.x"),
+            Self::MultipleFileHeader => write!(f, "additional file header chunk, ignored"),
+            Self::UnknownChunk => write!(f, "chunk with an unknown tag, skipped"),
+            Self::TruncatedChunk => write!(f, "chunk cut off by the end of the file"),
+            Self::UnparsedBytes => write!(f, "could not parse the chunk, ignored the rest of the file"),
+            Self::SkippedBytes => write!(f, "could not parse the chunk, skipped to the next boundary"),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.kind)?;
+        if let Some(stream_id) = self.stream_id {
+            write!(f, " (stream {stream_id})")?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " at byte offset {offset}")?;
+        }
+        Ok(())
+    }
+}
@@ -1,18 +1,28 @@
 //! Errors that can occur when parsing a chunk
-use std::sync::Arc;
 use thiserror::Error;
 
+use crate::ChunkKind;
+
+/// The error type returned by this crate.
 #[derive(Debug, Error)]
 pub enum XDFError {
+    /// An XML element could not be read or written.
     #[error(transparent)]
     Xml(#[from] XMLError),
 
+    /// The chunks could not be combined into valid streams.
     #[error(transparent)]
     Stream(#[from] StreamError),
 
+    /// The file could not be parsed.
     #[error(transparent)]
     Parse(#[from] ParseError),
 
+    /// A file could not be written.
+    #[error(transparent)]
+    Write(#[from] WriteError),
+
+    /// An underlying read or write failed.
     #[error(transparent)]
     IO(#[from] std::io::Error),
 }
@@ -24,58 +34,278 @@ pub enum XMLError {
 
     #[error(transparent)]
     ParseError(#[from] xmltree::ParseError),
+
+    #[error(transparent)]
+    WriteError(#[from] xmltree::Error),
 }
 
+/// An error in the structure of the file, as opposed to the contents of a single chunk.
 #[derive(Debug, Error)]
 pub enum StreamError {
+    /// A chunk belongs to a stream without a stream header.
     #[error("Could not find stream header chunk for stream id {0}")]
     MissingHeader(u32),
 
+    /// A stream has no stream footer.
     #[error("Could not find stream footer chunk for stream id {0}")]
     MissingFooter(u32),
 
-    #[error("Could not find file header chunk")]
+    /// The file ends right after the magic number, so it has no file header.
+    #[error("The file does not contain a file header chunk")]
     MissingFileHeader,
 
+    /// The first chunk of the file is not a file header.
+    #[error(
+        "The file starts with a {} chunk at byte offset {offset} instead of a file header chunk",
+        kind.map_or_else(|| "unknown".to_string(), |kind| kind.to_string())
+    )]
+    FileHeaderNotFirst {
+        /// The kind of the first chunk, or None if its tag is not defined by the specification.
+        kind: Option<ChunkKind>,
+        /// The byte offset of the first chunk from the start of the file.
+        offset: u64,
+    },
+
+    /// The file contains more than one file header chunk, see [`crate::ParseOptions::recover`].
     #[error("Multiple file header chunks found")]
     MultipleFileHeader,
 
+    /// The file has an XDF version other than 1.x, see [`crate::ParseOptions::allow_unknown_versions`].
     #[error("Version {0} is not supported")]
     UnsupportedVersion(f32),
 }
 
+/// An error while parsing the file.
 #[derive(Debug, Error)]
 pub enum ParseError {
+    /// A chunk could not be parsed.
     #[error("Error parsing chunk")]
     ChunkParse,
 
+    /// A chunk could not be read.
     #[error("Error reading chunk")]
     ChunkRead,
 
+    /// The file does not start with `XDF:`.
     #[error("File does not begin with magic number")]
     NoMagicNumber,
 
+    /// A chunk has a tag which is not defined by the specification.
     #[error("Invalid tag: {0}")]
     InvalidTag(u16),
 
+    /// A length is encoded with a number of bytes other than 1, 4 or 8.
     #[error("Invalid number of count bytes. Expected 1, 4, or 8, but got {0}")]
     InvalidNumCountBytes(u8),
 
+    /// A sample is invalid.
     #[error("There is something wrong with the samples")]
     InvalidSample,
 
+    /// The clock offsets of a stream are not in order.
     #[error("Encountered an invalid clock offset")]
     InvalidClockOffset,
 
+    /// A slice had the wrong length.
     #[error(transparent)]
     TryFromSlice(#[from] std::array::TryFromSliceError),
 
+    /// A string is not valid UTF-8.
     #[error(transparent)]
     Utf8(#[from] std::str::Utf8Error),
 
+    /// A number could not be parsed from a string.
     #[error(transparent)]
     ParseFloat(#[from] std::num::ParseFloatError),
 
+    /// A chunk could not be parsed, see [`ChunkError`].
     #[error(transparent)]
-    Nom(#[from] nom::Err<nom::error::Error<Arc<[u8]>>>),
+    Chunk(#[from] ChunkError),
+}
+
+/// A chunk which could not be parsed, with where and why.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error(
+    "Could not parse {}chunk #{chunk_index} at byte offset {offset}: {reason}",
+    .kind.map(|kind| format!("{kind} ")).unwrap_or_default()
+)]
+pub struct ChunkError {
+    /// The byte offset from the start of the file at which parsing failed.
+    pub offset: u64,
+    /// The byte offset from the start of the file at which the chunk starts.
+    pub chunk_offset: u64,
+    /// The index of the chunk in the file, starting at 0 for the file header.
+    pub chunk_index: u64,
+    /// The kind of the chunk, or None if its tag is missing or unknown.
+    pub kind: Option<ChunkKind>,
+    /// Why the chunk could not be parsed.
+    pub reason: ParseErrorReason,
+    /// The bytes at `offset`, at most [`ChunkError::EXCERPT_LEN`] of them, so that the rest of the input
+    /// doesn't have to be kept around.
+    pub excerpt: Vec<u8>,
+}
+
+impl ChunkError {
+    /// The maximum length of [`ChunkError::excerpt`].
+    pub const EXCERPT_LEN: usize = 16;
+}
+
+/// Why a chunk could not be parsed, as part of a [`ChunkError`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParseErrorReason {
+    /// The chunk or a value in it ends too early, for example because the file is cut off.
+    #[error("unexpected end of the chunk")]
+    UnexpectedEnd,
+
+    /// The bytes are not what they should be at this point.
+    #[error("unexpected bytes")]
+    UnexpectedBytes,
+
+    /// A length is encoded with a number of bytes other than 1, 4 or 8.
+    #[error("invalid number of length bytes {0}, expected 1, 4 or 8")]
+    InvalidNumLengthBytes(u8),
+
+    /// A length does not fit into memory on this platform.
+    #[error("length {0} is too large")]
+    LengthTooLarge(u64),
+
+    /// The tag of the chunk is 0, which no kind of chunk has. Chunks with other unknown tags are read as
+    /// [`crate::Chunk::Unknown`].
+    #[error("invalid chunk tag {0}")]
+    UnknownTag(u16),
+
+    /// A samples chunk belongs to a stream without a stream header before it.
+    #[error("samples for unknown stream {0}")]
+    UnknownStream(u32),
+
+    /// The `channel_format` of a stream header is not one of the formats defined by the specification.
+    #[error("channel_format '{0}' unsupported")]
+    UnsupportedFormat(String),
+
+    /// A required element of a stream header is missing or invalid.
+    #[error("stream header has no valid {0}")]
+    InvalidStreamHeader(&'static str),
+
+    /// The XML of a header or footer could not be parsed.
+    #[error("invalid XML: {0}")]
+    InvalidXml(String),
+
+    /// The file header has no valid version.
+    #[error("file header has no valid version")]
+    InvalidVersion,
+
+    /// A sample's number of timestamp bytes is neither 0 nor 8.
+    #[error("invalid number of timestamp bytes {0}, expected 0 or 8")]
+    InvalidTimestampBytes(u8),
+
+    /// A string value is not valid UTF-8.
+    #[error("string value is not valid UTF-8")]
+    InvalidUtf8,
+
+    /// A boundary chunk does not contain the boundary UUID.
+    #[error("boundary chunk does not contain the boundary UUID")]
+    InvalidBoundary,
+
+    /// One of the [`crate::ParseLimits`] was exceeded.
+    #[error("{limit} of {value} exceeds the limit of {max}")]
+    LimitExceeded {
+        /// Which limit was exceeded.
+        limit: Limit,
+        /// The value found in the file.
+        value: u64,
+        /// The configured maximum.
+        max: u64,
+    },
+}
+
+/// One of the [`crate::ParseLimits`], as part of [`ParseErrorReason::LimitExceeded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Limit {
+    /// [`crate::ParseLimits::max_chunk_size`]
+    ChunkSize,
+    /// [`crate::ParseLimits::max_samples_per_chunk`]
+    SamplesPerChunk,
+    /// [`crate::ParseLimits::max_channels`]
+    Channels,
+    /// [`crate::ParseLimits::max_string_length`]
+    StringLength,
+    /// [`crate::ParseLimits::max_decoded_bytes`]
+    DecodedBytes,
+}
+
+impl std::fmt::Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ChunkSize => write!(f, "chunk size"),
+            Self::SamplesPerChunk => write!(f, "number of samples in a chunk"),
+            Self::Channels => write!(f, "channel count"),
+            Self::StringLength => write!(f, "string length"),
+            Self::DecodedBytes => write!(f, "total size of the decoded samples"),
+        }
+    }
+}
+
+impl ParseErrorReason {
+    // whether the file is broken in a way that can't be the result of a cut-off or otherwise damaged chunk,
+    // so that loading it should fail instead of just stopping at this chunk
+    pub(crate) const fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            Self::LengthTooLarge(_)
+                | Self::LimitExceeded { .. }
+                | Self::UnsupportedFormat(_)
+                | Self::InvalidStreamHeader(_)
+                | Self::InvalidVersion
+                | Self::InvalidTimestampBytes(_)
+        )
+    }
+}
+
+/// An error while writing a file, see [`crate::XDFWriter`] and [`crate::XDFFile::write_to`].
+#[derive(Debug, Error)]
+pub enum WriteError {
+    /// A stream header is missing its channel count, sampling rate or channel format, or one of them is invalid.
+    #[error("The stream header must contain a valid channel_count, nominal_srate and channel_format")]
+    InvalidStreamHeader,
+
+    /// Samples or clock offsets were pushed to a stream that was not added to this writer.
+    #[error("There is no stream with id {0} in this writer")]
+    UnknownStream(u32),
+
+    /// More streams were added than there are stream IDs.
+    #[error("Too many streams")]
+    TooManyStreams,
+
+    /// A sample does not have one value per channel of its stream.
+    #[error("Stream {stream_id} has {expected} channels, but a sample has {got} values")]
+    ChannelCountMismatch {
+        /// The ID of the stream.
+        stream_id: u32,
+        /// The channel count of the stream.
+        expected: u32,
+        /// The number of values of the sample.
+        got: usize,
+    },
+
+    /// The values of a sample do not have the channel format of their stream.
+    #[error("Stream {stream_id} has format {expected:?}, but a sample has values of format {got:?}")]
+    FormatMismatch {
+        /// The ID of the stream.
+        stream_id: u32,
+        /// The channel format of the stream.
+        expected: crate::Format,
+        /// The format of the values of the sample.
+        got: crate::Format,
+    },
+
+    /// A stream has raw timestamps, but not one for each of its samples, see [`crate::Stream::raw_timestamps`].
+    #[error("Stream {stream_id} has {samples} samples, but {raw_timestamps} raw timestamps")]
+    TimestampCountMismatch {
+        /// The ID of the stream.
+        stream_id: u32,
+        /// The number of samples of the stream.
+        samples: usize,
+        /// The number of raw timestamps of the stream.
+        raw_timestamps: usize,
+    },
 }
@@ -0,0 +1,358 @@
+use std::fmt;
+
+use xmltree::{Element, XMLNode};
+
+use crate::FileHeaderChunk;
+
+// the file header as written by LabRecorder:
+// <info>
+//     <version>1.0</version>
+//     <datetime>2019-06-26T14:13:46+0200</datetime>
+//     ... user-defined session info
+// </info>
+
+/// The file header of an XDF file, see [`crate::XDFFile::header`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileHeader {
+    /// XDF version. Currently only 1.0 exists according to the specification.
+    pub version: f32,
+    /// When the recording was started, if the header gives a valid `datetime`.
+    pub datetime: Option<DateTime>,
+    /// All other elements of the header, for example user-defined information about the session.
+    /// A `datetime` which could not be parsed is kept here as well.
+    pub extra: Vec<Element>,
+}
+
+impl FileHeader {
+    /// A file header for the given version, without a recording start time or other elements.
+    #[must_use]
+    pub const fn new(version: f32) -> Self {
+        Self {
+            version,
+            datetime: None,
+            extra: Vec::new(),
+        }
+    }
+
+    /// The header as an XML element, as it is written to the file.
+    #[must_use]
+    pub fn to_xml(&self) -> Element {
+        let text_element = |name: &str, text: String| {
+            let mut element = Element::new(name);
+            element.children.push(XMLNode::Text(text));
+            XMLNode::Element(element)
+        };
+
+        let mut xml = Element::new("info");
+        // Debug keeps the decimal point of versions like 1.0
+        xml.children
+            .push(text_element("version", format!("{:?}", self.version)));
+        if let Some(datetime) = &self.datetime {
+            xml.children.push(text_element("datetime", datetime.to_string()));
+        }
+        xml.children.extend(self.extra.iter().cloned().map(XMLNode::Element));
+        xml
+    }
+}
+
+impl From<FileHeaderChunk> for FileHeader {
+    fn from(chunk: FileHeaderChunk) -> Self {
+        let mut datetime = None;
+        let mut extra = Vec::new();
+
+        for element in chunk.xml.children.into_iter().filter_map(|node| match node {
+            XMLNode::Element(element) => Some(element),
+            _ => None,
+        }) {
+            match element.name.as_str() {
+                "version" => {}
+                "datetime" if datetime.is_none() => {
+                    datetime = element.get_text().and_then(|text| DateTime::parse(&text));
+                    if datetime.is_none() {
+                        extra.push(element);
+                    }
+                }
+                _ => extra.push(element),
+            }
+        }
+
+        Self {
+            version: chunk.version,
+            datetime,
+            extra,
+        }
+    }
+}
+
+/**
+A date and time as written to the `datetime` of a file header, for example `2019-06-26T14:13:46+0200`.
+
+The fields are the local time of the recording computer. Combined with the offset from UTC they give the absolute time,
+see [`DateTime::unix_time`].
+
+# Example
+```rust
+# use xdf::DateTime;
+let datetime = DateTime::parse("2019-06-26T14:13:46+0200").unwrap();
+assert_eq!(datetime.hour, 14);
+assert_eq!(datetime.utc_offset_minutes, Some(120));
+assert_eq!(datetime.unix_time(), 1_561_551_226.0);
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    /// The year, for example 2019.
+    pub year: i32,
+    /// The month, from 1 to 12.
+    pub month: u8,
+    /// The day of the month, from 1 to 31.
+    pub day: u8,
+    /// The hour, from 0 to 23.
+    pub hour: u8,
+    /// The minute, from 0 to 59.
+    pub minute: u8,
+    /// The second, from 0 to 60 to allow for leap seconds.
+    pub second: u8,
+    /// The fraction of the second in nanoseconds.
+    pub nanosecond: u32,
+    /// The offset of the local time from UTC in minutes, or None if the time zone is not given.
+    pub utc_offset_minutes: Option<i16>,
+}
+
+impl DateTime {
+    /**
+    Parse an ISO 8601 date and time like `2019-06-26T14:13:46+0200`.
+    The seconds, their fraction and the offset from UTC (`Z`, `+02`, `+0200` or `+02:00`) are optional,
+    and the date and time may be separated by a space instead of `T`. Returns None if the text is not such a date and time.
+    */
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (date, time) = text.split_once(['T', ' '])?;
+
+        let mut date_parts = date.splitn(3, '-');
+        let year = parse_digits(date_parts.next()?, 4)?;
+        let month = parse_digits(date_parts.next()?, 2)?;
+        let day = parse_digits(date_parts.next()?, 2)?;
+
+        let (time, utc_offset_minutes) = split_utc_offset(time)?;
+        let (time, fraction) = match time.split_once(['.', ',']) {
+            Some((time, fraction)) => (time, Some(fraction)),
+            None => (time, None),
+        };
+        let mut time_parts = time.splitn(3, ':');
+        let hour = parse_digits(time_parts.next()?, 2)?;
+        let minute = parse_digits(time_parts.next()?, 2)?;
+        let (second, nanosecond) = match (time_parts.next(), fraction) {
+            (Some(second), fraction) => (parse_digits(second, 2)?, fraction.map_or(Some(0), parse_fraction)?),
+            (None, None) => (0, 0),
+            // only seconds can have a fraction
+            (None, Some(_)) => return None,
+        };
+
+        let datetime = Self {
+            year: i32::try_from(year).ok()?,
+            month: u8::try_from(month).ok()?,
+            day: u8::try_from(day).ok()?,
+            hour: u8::try_from(hour).ok()?,
+            minute: u8::try_from(minute).ok()?,
+            second: u8::try_from(second).ok()?,
+            nanosecond,
+            utc_offset_minutes,
+        };
+
+        let is_valid = (1..=12).contains(&datetime.month)
+            && (1..=days_in_month(datetime.year, datetime.month)).contains(&datetime.day)
+            && datetime.hour < 24
+            && datetime.minute < 60
+            && datetime.second <= 60;
+        is_valid.then_some(datetime)
+    }
+
+    /// The time in seconds since the Unix epoch (1970-01-01 00:00 UTC).
+    /// A time without an offset from UTC is taken to be in UTC.
+    #[must_use]
+    pub fn unix_time(&self) -> f64 {
+        let days = days_from_civil(self.year, self.month, self.day);
+        let seconds = ((days * 24 + i64::from(self.hour)) * 60 + i64::from(self.minute)) * 60 + i64::from(self.second)
+            - i64::from(self.utc_offset_minutes.unwrap_or(0)) * 60;
+        seconds as f64 + f64::from(self.nanosecond) * 1E-9
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )?;
+        if self.nanosecond > 0 {
+            let fraction = format!("{:09}", self.nanosecond);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        if let Some(offset) = self.utc_offset_minutes {
+            let sign = if offset < 0 { '-' } else { '+' };
+            let offset = offset.unsigned_abs();
+            write!(f, "{sign}{:02}:{:02}", offset / 60, offset % 60)?;
+        }
+        Ok(())
+    }
+}
+
+// parses a number of exactly the given number of ASCII digits
+fn parse_digits(text: &str, digits: usize) -> Option<u32> {
+    if text.len() != digits || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+// parses the fraction of a second, digits after the ninth are ignored
+fn parse_fraction(fraction: &str) -> Option<u32> {
+    if fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let nine_digits = format!("{:0<9}", &fraction[..fraction.len().min(9)]);
+    nine_digits.parse().ok()
+}
+
+// splits the time from its offset from UTC in minutes, if it has one
+fn split_utc_offset(time: &str) -> Option<(&str, Option<i16>)> {
+    if let Some(time) = time.strip_suffix('Z') {
+        return Some((time, Some(0)));
+    }
+    let Some(sign_index) = time.rfind(['+', '-']) else {
+        return Some((time, None));
+    };
+
+    let (time, offset) = time.split_at(sign_index);
+    let sign = if offset.starts_with('-') { -1 } else { 1 };
+    let offset = offset[1..].replace(':', "");
+    let (hours, minutes) = match offset.len() {
+        2 => (parse_digits(&offset, 2)?, 0),
+        4 => (parse_digits(&offset[..2], 2)?, parse_digits(&offset[2..], 2)?),
+        _ => return None,
+    };
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+
+    let minutes = i16::try_from(hours * 60 + minutes).ok()?;
+    Some((time, Some(sign * minutes)))
+}
+
+const fn is_leap_year(year: i32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+const fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// the number of days since 1970-01-01 of a date in the proleptic Gregorian calendar, after Howard Hinnant's algorithm
+fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)] // whole seconds are exactly representable
+    fn test_parse_datetime() {
+        let datetime = DateTime::parse("2019-06-26T14:13:46+0200").unwrap();
+        assert_eq!(
+            datetime,
+            DateTime {
+                year: 2019,
+                month: 6,
+                day: 26,
+                hour: 14,
+                minute: 13,
+                second: 46,
+                nanosecond: 0,
+                utc_offset_minutes: Some(120),
+            }
+        );
+        assert_eq!(datetime.unix_time(), 1_561_551_226.0);
+        assert_eq!(DateTime::parse(&datetime.to_string()), Some(datetime));
+
+        let utc = DateTime::parse("2000-02-29 23:59:59.25Z").unwrap();
+        assert_eq!(utc.nanosecond, 250_000_000);
+        assert_eq!(utc.unix_time(), 951_868_799.25);
+        assert_eq!(utc.to_string(), "2000-02-29T23:59:59.25+00:00");
+
+        let negative = DateTime::parse("1969-12-31T20:00-04:00").unwrap();
+        assert_eq!(negative.unix_time(), 0.0);
+        assert_eq!(DateTime::parse("2024-01-01T00:00").unwrap().utc_offset_minutes, None);
+
+        for invalid in [
+            "",
+            "2019-06-26",
+            "2019-02-29T00:00",
+            "2019-06-26T24:00",
+            "19-06-26T14:13",
+            "2019-06-26T14:13+2",
+        ] {
+            assert_eq!(DateTime::parse(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_file_header() {
+        let text = "<info><version>1.0</version><datetime>2019-06-26T14:13:46+02:00</datetime>\
+                <session><subject>P01</subject></session></info>";
+        let xml = Element::parse(text.as_bytes()).unwrap();
+        let header = FileHeader::from(FileHeaderChunk {
+            version: 1.0,
+            xml: xml.clone(),
+        });
+
+        // the version and datetime are only kept in their fields
+        assert_eq!(header.datetime.unwrap().year, 2019);
+        assert_eq!(header.extra.len(), 1);
+        assert_eq!(header.extra[0].name, "session");
+        assert_eq!(header.to_xml(), xml);
+
+        let mut changed = header.clone();
+        changed.version = 1.1;
+        changed.datetime = None;
+        let changed_xml = changed.to_xml();
+        assert_eq!(changed_xml.get_child("version").unwrap().get_text().unwrap(), "1.1");
+        assert!(changed_xml.get_child("datetime").is_none());
+        assert!(changed_xml.get_child("session").is_some());
+
+        let xml =
+            Element::parse("<info><version>1.0</version><datetime>yesterday</datetime></info>".as_bytes()).unwrap();
+        let header = FileHeader::from(FileHeaderChunk {
+            version: 1.0,
+            xml: xml.clone(),
+        });
+        assert_eq!(header.datetime, None);
+        assert_eq!(header.extra[0].name, "datetime");
+        assert_eq!(header.to_xml(), xml);
+
+        let mut new = FileHeader::new(1.0);
+        new.datetime = DateTime::parse("2019-06-26T14:13:46+0200");
+        let new_xml = new.to_xml();
+        let names: Vec<&str> = new_xml
+            .children
+            .iter()
+            .filter_map(|node| node.as_element().map(|element| element.name.as_str()))
+            .collect();
+        assert_eq!(names, ["version", "datetime"]);
+    }
+}
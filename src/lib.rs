@@ -15,12 +15,12 @@
 //! [github]: https://img.shields.io/badge/github-9090ff?style=for-the-badge&logo=github&labelColor=555555
 //! [crates]: https://img.shields.io/badge/crates.io-fc8d62?style=for-the-badge&labelColor=555555&logo=rust
 //!
-//! Read (and maybe one day write) XDF files
+//! Read and write XDF files
 //! Currently the only supported XDF version is 1.0. (at the time of writing, this the only version that exists)
 //!
 //! [`XDF format specification`]: https://github.com/sccn/xdf/wiki/Specifications
 //!
-//! This library provides a way to read files in the [`XDF format`] as specified by SCCN.
+//! This library provides a way to read and write files in the [`XDF format`] as specified by SCCN.
 //!
 //! # Example
 //! ```rust
@@ -33,28 +33,50 @@
 //!# }
 //!```
 
+mod channels;
+mod chunk_reader;
 mod chunk_structs;
+mod diagnostics;
 mod errors;
+mod file_header;
+mod montage;
+mod options;
 mod parsers;
 mod sample;
+mod segments;
 mod streams;
+mod summary;
+mod sync;
 mod util;
+mod writers;
+mod xdf_writer;
 
-use log::warn;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufReader, Read, Write};
 use std::iter::Iterator;
+use std::ops::Range;
 use std::sync::Arc;
 
-pub use errors::XDFError;
+pub use channels::ChannelInfo;
+pub use chunk_reader::{ChunkReader, LocatedChunk, Truncation};
+pub use chunk_structs::{
+    BoundaryChunk, Chunk, ChunkKind, ClockOffsetChunk, FileHeaderChunk, SamplesChunk, StreamFooterChunk,
+    StreamFooterChunkInfo, StreamHeaderChunk, StreamHeaderChunkInfo, UnknownChunk,
+};
+pub use diagnostics::{Diagnostic, DiagnosticKind, Severity};
+pub use errors::{ChunkError, Limit, ParseError, ParseErrorReason, StreamError, WriteError, XDFError};
+pub use file_header::{DateTime, FileHeader};
+pub use montage::{CapInfo, ElectrodePosition, Montage};
+pub use options::{ClockSync, ParseLimits, ParseOptions, Segmentation};
 pub use sample::Sample;
+pub use segments::Segment;
 pub use streams::Stream;
+pub use summary::{StreamSummary, XDFSummary};
+pub use sync::ClockOffsetStats;
+pub use xdf_writer::{StreamHandle, XDFWriter, DEFAULT_BOUNDARY_INTERVAL};
 
-use chunk_structs::{BoundaryChunk, ClockOffsetChunk, FileHeaderChunk, StreamFooterChunk, StreamHeaderChunk};
-use errors::{ParseError, StreamError};
 use util::FiniteF64;
-
-use crate::chunk_structs::Chunk;
-use crate::parsers::xdf_file::xdf_file_parser;
+use writers::FooterStats;
 
 type StreamID = u32;
 type SampleIter = std::vec::IntoIter<Sample>;
@@ -64,11 +86,24 @@ type SampleIter = std::vec::IntoIter<Sample>;
 #[derive(Debug, Clone, PartialEq)]
 pub struct XDFFile {
     /// XDF version. Currently only 1.0 exists according to the specification.
+    /// The same as the version of the [`XDFFile::header`], which is the one written to the file.
+    #[deprecated(note = "use `header.version` instead")]
     pub version: f32,
-    /// The XML header of the XDF file as an [`xmltree::Element`].
-    pub header: xmltree::Element,
-    /// A vector of streams contained in the XDF file.
+    /// The file header with the XDF version, the time the recording was started and the other elements of its XML.
+    pub header: FileHeader,
+    /// The streams contained in the XDF file, in the order of their headers in the file, see [`Stream::header_offset`].
     pub streams: Vec<Stream>,
+    /// The byte ranges that were skipped because they could not be parsed, see [`ParseOptions::recover`].
+    /// Always empty if the file was not parsed in recovery mode.
+    pub skipped_ranges: Vec<Range<u64>>,
+    /// The chunk which was cut off by the end of the file, if the file ended in the middle of a chunk.
+    /// See [`ParseOptions::lenient`] for loading as much of it as possible.
+    pub truncation: Option<Truncation>,
+    /// The problems found while loading the file which did not stop it from being loaded, in the order they were found.
+    pub diagnostics: Vec<Diagnostic>,
+    /// The chunks with a tag which is not defined by the specification, in the order they appear in the file.
+    /// Each one is reported with [`DiagnosticKind::UnknownChunk`] at its offset.
+    pub unknown_chunks: Vec<UnknownChunk>,
 }
 
 /// Possible formats for the data in a stream as given in the specification.
@@ -103,10 +138,50 @@ pub enum Values {
     String(String),
 }
 
+impl Values {
+    /// The [`Format`] of these values.
+    #[must_use]
+    pub const fn format(&self) -> Format {
+        match self {
+            Self::Int8(_) => Format::Int8,
+            Self::Int16(_) => Format::Int16,
+            Self::Int32(_) => Format::Int32,
+            Self::Int64(_) => Format::Int64,
+            Self::Float32(_) => Format::Float32,
+            Self::Float64(_) => Format::Float64,
+            Self::String(_) => Format::String,
+        }
+    }
+
+    // the number of values, a string counts as one
+    pub(crate) fn num_values(&self) -> usize {
+        match self {
+            Self::Int8(v) => v.len(),
+            Self::Int16(v) => v.len(),
+            Self::Int32(v) => v.len(),
+            Self::Int64(v) => v.len(),
+            Self::Float32(v) => v.len(),
+            Self::Float64(v) => v.len(),
+            Self::String(_) => 1,
+        }
+    }
+}
+
 struct GroupedChunks {
+    file_header_chunk: FileHeaderChunk,
+    stream_chunks: StreamChunks,
+}
+
+#[derive(Default)]
+struct StreamChunks {
     stream_header_chunks: Vec<StreamHeaderChunk>,
     stream_footer_chunks: Vec<StreamFooterChunk>,
+    // the stream IDs and offsets of the stream headers and footers in the order they appear in the file
+    header_offsets: Vec<(StreamID, u64)>,
+    footer_offsets: Vec<(StreamID, u64)>,
     clock_offsets: HashMap<StreamID, Vec<ClockOffsetChunk>>,
+    // the number of clock offsets of each stream which were dropped as they are not finite
+    rejected_clock_offsets: HashMap<StreamID, usize>,
     sample_map: HashMap<StreamID, Vec<SampleIter>>,
 }
 
@@ -131,126 +206,587 @@ impl XDFFile {
     ```
     */
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, XDFError> {
-        // this error mapping could use some simplification
-        let (input, chunks) = xdf_file_parser(bytes)
-            .map_err(|e| match e {
-                // we have to map the error to use Arc instead of slice because we would otherwise need a static lifetime.
-                nom::Err::Incomplete(n) => nom::Err::Incomplete(n),
-                nom::Err::Error(nom::error::Error { input, code }) => nom::Err::Error(nom::error::Error {
-                    input: Arc::from(input.to_owned()),
-                    code,
-                }),
-                nom::Err::Failure(nom::error::Error { input, code }) => nom::Err::Failure(nom::error::Error {
-                    input: Arc::from(input.to_owned()),
-                    code,
-                }),
-            })
-            .map_err(ParseError::from)?;
-
-        // we don't error here to be more error tolerant and allow for partial parsing
-        if !input.is_empty() {
-            warn!("There are {} bytes left in the input after parsing.", input.len());
+        Self::from_bytes_with(bytes, &ParseOptions::default())
+    }
+
+    /**
+    Parse an XDF file from a byte slice, using the given [`ParseOptions`].
+    # Arguments
+    * `bytes` - A byte slice of the whole XDF file as read from disk.
+    * `options` - The options to parse the file with.
+    # Returns
+    * A Result containing the parsed [`XDFFile`] or an [`XDFError`]
+    # Errors
+    Will error if the file could not be parsed correctly for various reasons. See [`XDFError`] for more information.
+    # Example
+    ```rust
+    # use std::fs;
+    # use xdf::{ParseOptions, XDFFile};
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let bytes = fs::read("tests/minimal.xdf")?;
+    let options = ParseOptions::new().select_streams(|header| header.info.name.as_deref() == Some("SendDataC"));
+    let xdf_file = XDFFile::from_bytes_with(&bytes, &options)?;
+    # Ok(())
+    # }
+    ```
+    */
+    pub fn from_bytes_with(bytes: &[u8], options: &ParseOptions) -> Result<Self, XDFError> {
+        Self::read_chunks(ChunkReader::new(bytes), options)
+    }
+
+    /**
+    Parse an XDF file from a reader, for example a [`std::fs::File`].
+
+    The file is read one chunk at a time, so only the decoded data and a single chunk are kept in memory.
+    The reader is buffered internally.
+    # Arguments
+    * `reader` - A reader positioned at the start of the XDF file.
+    # Returns
+    * A Result containing the parsed [`XDFFile`] or an [`XDFError`]
+    # Errors
+    Will error if reading fails or if the file could not be parsed correctly for various reasons. See [`XDFError`] for more information.
+    # Example
+    ```rust
+    # use std::fs::File;
+    # use xdf::XDFFile;
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let file = File::open("tests/minimal.xdf")?;
+    let xdf_file = XDFFile::from_reader(file)?;
+    # Ok(())
+    # }
+    ```
+    */
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, XDFError> {
+        Self::from_reader_with(reader, &ParseOptions::default())
+    }
+
+    /**
+    Parse an XDF file from a reader, using the given [`ParseOptions`].
+
+    See [`XDFFile::from_reader`] and [`XDFFile::from_bytes_with`].
+    # Errors
+    Will error if reading fails or if the file could not be parsed correctly for various reasons. See [`XDFError`] for more information.
+    */
+    pub fn from_reader_with<R: Read>(reader: R, options: &ParseOptions) -> Result<Self, XDFError> {
+        Self::read_chunks(ChunkReader::new(BufReader::new(reader)), options)
+    }
+
+    /**
+    Quickly read an overview of an XDF file from a byte slice, without decoding any samples.
+
+    Only the file header, stream headers and stream footers are parsed, samples chunks are skipped using their length.
+    # Arguments
+    * `bytes` - A byte slice of the whole XDF file as read from disk.
+    # Returns
+    * A Result containing the [`XDFSummary`] or an [`XDFError`]
+    # Errors
+    Will error if the file could not be parsed correctly for various reasons. See [`XDFError`] for more information.
+    # Example
+    ```rust
+    # use std::fs;
+    # use xdf::XDFFile;
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let bytes = fs::read("tests/minimal.xdf")?;
+    let summary = XDFFile::probe(&bytes)?;
+    for stream in &summary.streams {
+        println!("{}: {:?} with {} channels", stream.id, stream.name, stream.channel_count);
+    }
+    # Ok(())
+    # }
+    ```
+    */
+    pub fn probe(bytes: &[u8]) -> Result<XDFSummary, XDFError> {
+        Self::probe_with(bytes, &ParseOptions::default())
+    }
+
+    /**
+    Quickly read an overview of an XDF file from a byte slice, without decoding any samples, using the given [`ParseOptions`].
+
+    See [`XDFFile::probe`]. Only the [`ParseOptions::limits`], [`ParseOptions::recover`] and
+    [`ParseOptions::allow_unknown_versions`] apply, as the other options concern the samples.
+    # Errors
+    Will error if the file could not be parsed correctly for various reasons. See [`XDFError`] for more information.
+    # Example
+    ```rust
+    # use std::fs;
+    # use xdf::{ParseLimits, ParseOptions, XDFFile};
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let bytes = fs::read("tests/minimal.xdf")?;
+    let limits = ParseLimits {
+        max_chunk_size: 1 << 20,
+        ..ParseLimits::default()
+    };
+    let summary = XDFFile::probe_with(&bytes, &ParseOptions::new().limits(limits))?;
+    # Ok(())
+    # }
+    ```
+    */
+    pub fn probe_with(bytes: &[u8], options: &ParseOptions) -> Result<XDFSummary, XDFError> {
+        summary::summarize(ChunkReader::new(bytes), options)
+    }
+
+    /**
+    Quickly read an overview of an XDF file from a reader, without decoding any samples.
+
+    See [`XDFFile::probe`]. The reader is buffered internally.
+    # Errors
+    Will error if reading fails or if the file could not be parsed correctly for various reasons. See [`XDFError`] for more information.
+    */
+    pub fn probe_reader<R: Read>(reader: R) -> Result<XDFSummary, XDFError> {
+        Self::probe_reader_with(reader, &ParseOptions::default())
+    }
+
+    /**
+    Quickly read an overview of an XDF file from a reader, without decoding any samples, using the given [`ParseOptions`].
+
+    See [`XDFFile::probe_reader`] and [`XDFFile::probe_with`].
+    # Errors
+    Will error if reading fails or if the file could not be parsed correctly for various reasons. See [`XDFError`] for more information.
+    */
+    pub fn probe_reader_with<R: Read>(reader: R, options: &ParseOptions) -> Result<XDFSummary, XDFError> {
+        summary::summarize(ChunkReader::new(BufReader::new(reader)), options)
+    }
+
+    fn read_chunks<R: Read>(chunk_reader: ChunkReader<R>, options: &ParseOptions) -> Result<Self, XDFError> {
+        let mut chunk_reader = chunk_reader
+            .recover(options.recover)
+            .decode_truncated(options.lenient)
+            .limits(options.limits)
+            .count_timestamp_columns(options.timestamp_columns);
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        let file_header_chunk = read_file_header(&mut chunk_reader, options.allow_unknown_versions, &mut diagnostics)?;
+        let mut grouped_chunks = GroupedChunks::new(file_header_chunk);
+        let mut skipped_streams: HashSet<StreamID> = HashSet::new();
+        let mut unknown_chunks: Vec<UnknownChunk> = Vec::new();
+
+        while let Some(located_chunk) = next_chunk(&mut chunk_reader, &mut diagnostics)? {
+            let chunk = &located_chunk.chunk;
+
+            if let Chunk::FileHeader(_) = chunk {
+                if !options.recover {
+                    return Err(StreamError::MultipleFileHeader.into());
+                }
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::MultipleFileHeader,
+                    None,
+                    Some(located_chunk.offset),
+                ));
+                continue;
+            }
+
+            if let Chunk::Unknown(unknown_chunk) = located_chunk.chunk {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::UnknownChunk,
+                    None,
+                    Some(located_chunk.offset),
+                ));
+                unknown_chunks.push(unknown_chunk);
+                continue;
+            }
+
+            if let Chunk::StreamHeader(stream_header) = chunk {
+                if !options.is_selected(stream_header) {
+                    chunk_reader.skip_samples_of(stream_header.stream_id);
+                    skipped_streams.insert(stream_header.stream_id);
+                }
+            }
+
+            if chunk.stream_id().is_some_and(|id| skipped_streams.contains(&id)) {
+                continue;
+            }
+
+            grouped_chunks.add(located_chunk, options, &mut diagnostics);
         }
 
-        let (file_header_chunk, grouped_chunks) = group_chunks(chunks)?;
+        let mut truncation = chunk_reader.truncation().cloned();
+        if let Some(truncation) = &truncation {
+            diagnostics.push(truncation_diagnostic(truncation));
+        }
 
-        let streams = process_streams(grouped_chunks)?;
+        let stream_chunks = &grouped_chunks.stream_chunks;
+        check_footers(
+            &stream_chunks.header_offsets,
+            &stream_chunks.footer_offsets,
+            &mut diagnostics,
+        );
 
-        Ok(Self {
-            version: file_header_chunk.version,
-            header: file_header_chunk.xml,
+        if let Some(truncation) = truncation.as_mut().filter(|_| options.lenient) {
+            truncation.synthesized_footers = grouped_chunks.stream_chunks.synthesize_missing_footers();
+            diagnostics.extend(
+                truncation
+                    .synthesized_footers
+                    .iter()
+                    .map(|&id| Diagnostic::new(DiagnosticKind::SynthesizedFooter, Some(id), None)),
+            );
+        }
+
+        let file_header_chunk = grouped_chunks.file_header_chunk;
+        let streams = process_streams(grouped_chunks.stream_chunks, options, &mut diagnostics)?;
+
+        let header = FileHeader::from(file_header_chunk);
+        #[allow(deprecated)]
+        let xdf_file = Self {
+            version: header.version,
+            header,
             streams,
-        })
+            skipped_ranges: chunk_reader.skipped_ranges().to_vec(),
+            truncation,
+            diagnostics,
+            unknown_chunks,
+        };
+        Ok(xdf_file)
+    }
+
+    /**
+    Serialize the XDF file to a byte vector.
+    # Returns
+    * A Result containing the bytes of a valid XDF file or an [`XDFError`]
+    # Errors
+    Will error if an XML element could not be written or if a sample does not match the format or channel count of its stream.
+    # Example
+    ```rust
+    # use std::fs;
+    # use xdf::XDFFile;
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let bytes = fs::read("tests/minimal.xdf")?;
+    let xdf_file = XDFFile::from_bytes(&bytes)?;
+    let written = xdf_file.to_bytes()?;
+    # Ok(())
+    # }
+    ```
+    */
+    pub fn to_bytes(&self) -> Result<Vec<u8>, XDFError> {
+        let mut bytes = Vec::new();
+        self.write_to(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /**
+    Write the XDF file to a writer.
+
+    The [`XDFFile::unknown_chunks`] are written right after the file header. All stream headers follow,
+    then a single samples chunk and the clock offsets of each stream, and the stream footers. The samples are written with their [`Stream::raw_timestamps`], so that reading the file back
+    corrects them just like they were before.
+
+    The timestamps of [`Stream::samples`] are not written. When adding or removing samples, add or remove their
+    recorded timestamps in [`Stream::raw_timestamps`] as well, and set [`Stream::clock_offsets`] to the offsets
+    they should be corrected with when the file is read. Streams without raw timestamps, for example because
+    [`ParseOptions::timestamp_columns`] was disabled, are written with the timestamps of their samples and
+    without clock offsets instead.
+    # Arguments
+    * `writer` - Where to write the file to, for example a [`std::fs::File`]. Consider wrapping it in a [`std::io::BufWriter`].
+    # Errors
+    Will error if writing fails, if an XML element could not be written, if a sample does not match the format or channel count of its stream,
+    or if a stream has raw timestamps, but a different number of them than samples.
+    */
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), XDFError> {
+        writers::xdf_file::xdf_file(&mut writer, self)
+    }
+
+    /**
+    Approximate the wall-clock time of a timestamp of a stream, in seconds since the Unix epoch.
+
+    This is only an approximation: timestamps are measured by a clock of the recording computer which does not relate
+    to the wall-clock time, and the file does not record which timestamp the [`FileHeader::datetime`] belongs to.
+    The recording start is therefore assumed to be the time of the earliest sample in the file. As recorders usually
+    write the file header just before the first samples arrive, the result is often within a second of the true time,
+    but it can be off by however long the recorder waited for the first sample.
+    None if the header gives no recording start or no sample has a timestamp.
+    # Example
+    ```rust
+    # use std::fs;
+    # use xdf::XDFFile;
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let bytes = fs::read("tests/minimal.xdf")?;
+    let xdf_file = XDFFile::from_bytes(&bytes)?;
+    let first_sample = &xdf_file.streams[0].samples[0];
+    if let Some(unix_time) = first_sample.timestamp.and_then(|ts| xdf_file.unix_time(ts)) {
+        println!("recorded {unix_time} s after the Unix epoch");
+    }
+    # Ok(())
+    # }
+    ```
+    */
+    #[must_use]
+    pub fn unix_time(&self, timestamp: f64) -> Option<f64> {
+        let recording_start = self.header.datetime?.unix_time();
+        let first_timestamp = self
+            .streams
+            .iter()
+            .filter_map(|stream| stream.samples.iter().find_map(|sample| sample.timestamp))
+            .reduce(f64::min)?;
+
+        Some(recording_start + (timestamp - first_timestamp))
     }
 }
 
-// takes a vector of chunks and sorts them into a GroupedChunks struct based on their type
-fn group_chunks(chunks: Vec<Chunk>) -> Result<(FileHeaderChunk, GroupedChunks), XDFError> {
-    let mut file_header_chunk: Option<FileHeaderChunk> = None;
-    let mut stream_header_chunks: Vec<StreamHeaderChunk> = Vec::new();
-    let mut stream_footer_chunks: Vec<StreamFooterChunk> = Vec::new();
-    let mut clock_offsets: HashMap<StreamID, Vec<ClockOffsetChunk>> = HashMap::new();
+// reads the first chunk, which must be the file header of a supported version unless unknown versions are allowed
+fn read_file_header<R: Read>(
+    chunk_reader: &mut ChunkReader<R>,
+    allow_unknown_versions: bool,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<FileHeaderChunk, XDFError> {
+    let (file_header_chunk, offset) = match chunk_reader.next().transpose()? {
+        Some(LocatedChunk {
+            chunk: Chunk::FileHeader(file_header_chunk),
+            offset,
+            ..
+        }) => (file_header_chunk, offset),
+        Some(LocatedChunk { chunk, offset, .. }) => {
+            return Err(StreamError::FileHeaderNotFirst {
+                kind: chunk.kind(),
+                offset,
+            }
+            .into());
+        }
+        None => return Err(StreamError::MissingFileHeader.into()),
+    };
 
-    // the sample_map maps stream IDs to a vector of iterators which each iterate over one chunk's samples
-    let sample_map = chunks
-        .into_iter()
-        .filter_map(|chunk_res| {
-            match chunk_res {
-                Chunk::FileHeader(c) => {
-                    file_header_chunk = Some(c);
-                    None
-                }
-                Chunk::StreamHeader(c) => {
-                    stream_header_chunks.push(c);
-                    None
-                }
-                Chunk::StreamFooter(c) => {
-                    stream_footer_chunks.push(c);
-                    None
-                }
-                Chunk::Samples(c) => Some(c), // pass only samples through to the fold
-                Chunk::ClockOffset(c) => {
-                    clock_offsets.entry(c.stream_id).or_default().push(c);
+    if !(1.0..2.0).contains(&file_header_chunk.version) {
+        if !allow_unknown_versions {
+            return Err(StreamError::UnsupportedVersion(file_header_chunk.version).into());
+        }
+        diagnostics.push(Diagnostic::new(DiagnosticKind::UnsupportedVersion, None, Some(offset)));
+    }
 
-                    None
+    Ok(file_header_chunk)
+}
+
+// reads the next chunk, treating a chunk that can't be parsed as the end of the file unless the reader recovers from it.
+// Only failures which can't just be the result of a cut-off or otherwise broken chunk are returned as errors.
+fn next_chunk<R: Read>(
+    chunk_reader: &mut ChunkReader<R>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<Option<LocatedChunk>, XDFError> {
+    loop {
+        let offset = chunk_reader.offset();
+        match chunk_reader.next().transpose() {
+            // exceeding a limit or a broken file isn't recovered from either
+            Err(XDFError::Parse(ParseError::Chunk(e))) if e.reason.is_fatal() => {
+                return Err(ParseError::Chunk(e).into());
+            }
+            Err(XDFError::Parse(_)) if chunk_reader.is_recovering() => {
+                diagnostics.push(Diagnostic::new(DiagnosticKind::SkippedBytes, None, Some(offset)));
+            }
+            // we don't error here to be more error tolerant and allow for partial parsing
+            Err(XDFError::Parse(ParseError::Chunk(e))) if !e.reason.is_fatal() => {
+                // a cut-off chunk is reported on its own
+                if chunk_reader.truncation().is_none_or(|t| t.offset != offset) {
+                    diagnostics.push(Diagnostic::new(DiagnosticKind::UnparsedBytes, None, Some(offset)));
                 }
-                Chunk::Boundary(_) => None, // boundary chunks are discarded for now
+                return Ok(None);
             }
-        })
-        .fold(
-            // fold the samples into a map of stream IDs to a vector of iterators so we can merge them later
-            HashMap::new(),
-            |mut map: HashMap<StreamID, Vec<SampleIter>>, chunk| {
-                map.entry(chunk.stream_id).or_default().push(chunk.samples.into_iter());
-                map
-            },
-        );
+            result => return result,
+        }
+    }
+}
 
-    let file_header_chunk = file_header_chunk.ok_or(StreamError::MissingFileHeader)?;
+// reports a footer which doesn't match the stream's samples and clock offsets.
+// The footer's timestamps are compared with the first and last timestamp before clock correction, allowing for one
+// sample interval (or a millisecond for irregular streams) as the footer may be rounded.
+fn check_footer_contents(stream: &Stream, interpolated: (Option<f64>, Option<f64>), diagnostics: &mut Vec<Diagnostic>) {
+    let Some(footer) = &stream.footer_info else {
+        return;
+    };
+    let mut report = |kind| diagnostics.push(Diagnostic::new(kind, Some(stream.id), None));
+
+    if footer
+        .sample_count
+        .is_some_and(|count| count != stream.samples.len() as u64)
+    {
+        report(DiagnosticKind::FooterSampleCountMismatch);
+    }
 
-    let info = GroupedChunks {
-        stream_header_chunks,
-        stream_footer_chunks,
-        clock_offsets,
-        sample_map,
+    let tolerance = stream
+        .nominal_srate
+        .filter(|srate| *srate > 0.0)
+        .map_or(1E-3, |srate| 1.0 / srate);
+    let differs = |expected: Option<f64>, actual: Option<f64>| match (expected, actual) {
+        (Some(expected), Some(actual)) => (expected - actual).abs() > tolerance,
+        (Some(_), None) => true,
+        (None, _) => false,
     };
+    let (first, last) = interpolated;
+    if differs(footer.first_timestamp, first) || differs(footer.last_timestamp, last) {
+        report(DiagnosticKind::FooterTimestampMismatch);
+    }
 
-    // yes I return these separately. It saves me a clone. Sue me.
-    Ok((file_header_chunk, info))
+    let has_chunk = |time: f64| {
+        stream
+            .clock_offsets
+            .iter()
+            .any(|&(collection_time, _)| (collection_time - time).abs() <= 1E-3)
+    };
+    // offsets which are not finite are reported on their own
+    let mut footer_times = footer
+        .clock_offsets
+        .iter()
+        .filter(|(time, value)| time.is_finite() && value.is_finite())
+        .map(|&(time, _)| time);
+    if !footer_times.all(has_chunk) {
+        report(DiagnosticKind::FooterClockOffsetMissing);
+    }
 }
 
-// takes grouped chunks and combines them into finished streams.
-fn process_streams(mut grouped_chunks: GroupedChunks) -> Result<Vec<Stream>, XDFError> {
-    let stream_header_map: HashMap<StreamID, StreamHeaderChunk> = grouped_chunks
-        .stream_header_chunks
-        .into_iter()
-        .map(|s| (s.stream_id, s))
-        .collect();
+fn truncation_diagnostic(truncation: &Truncation) -> Diagnostic {
+    Diagnostic::new(
+        DiagnosticKind::TruncatedChunk,
+        truncation.stream_id,
+        Some(truncation.offset),
+    )
+}
 
-    let mut stream_footer_map: HashMap<StreamID, StreamFooterChunk> = grouped_chunks
-        .stream_footer_chunks
-        .into_iter()
-        .map(|s| (s.stream_id, s))
-        .collect();
+// reports the streams with a header but no footer and the other way around.
+// Both are allowed to be more error tolerant and not lose all experimental data if the recording stops unexpectedly.
+fn check_footers(
+    header_offsets: &[(StreamID, u64)],
+    footer_offsets: &[(StreamID, u64)],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for &(stream_id, offset) in header_offsets {
+        if !footer_offsets.iter().any(|&(id, _)| id == stream_id) {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::MissingFooter,
+                Some(stream_id),
+                Some(offset),
+            ));
+        }
+    }
 
-    // this can happen if the recording stops unexpectedly.
-    // We allow this to be more error tolerant and not lose all experimental data.
-    for &stream_id in stream_header_map.keys() {
-        if !stream_footer_map.contains_key(&stream_id) {
-            warn!("Stream header without corresponding stream footer for id: {stream_id}");
+    for &(stream_id, offset) in footer_offsets {
+        if !header_offsets.iter().any(|&(id, _)| id == stream_id) {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::FooterWithoutHeader,
+                Some(stream_id),
+                Some(offset),
+            ));
         }
     }
+}
 
-    // this on the other hand is a bit weirder but again, we allow it to be more error tolerant
-    for &stream_id in stream_footer_map.keys() {
-        if !stream_header_map.contains_key(&stream_id) {
-            warn!("Stream footer without corresponding stream header for id: {stream_id}");
+impl GroupedChunks {
+    fn new(file_header_chunk: FileHeaderChunk) -> Self {
+        Self {
+            file_header_chunk,
+            stream_chunks: StreamChunks::default(),
         }
     }
 
+    // sorts a chunk into the right collection based on its type
+    fn add(&mut self, located_chunk: LocatedChunk, options: &ParseOptions, diagnostics: &mut Vec<Diagnostic>) {
+        let stream_chunks = &mut self.stream_chunks;
+        let offset = located_chunk.offset;
+        match located_chunk.chunk {
+            Chunk::StreamHeader(c) => {
+                stream_chunks.header_offsets.push((c.stream_id, offset));
+                stream_chunks.stream_header_chunks.push(c);
+            }
+            Chunk::StreamFooter(c) => {
+                stream_chunks.footer_offsets.push((c.stream_id, offset));
+                stream_chunks.stream_footer_chunks.push(c);
+            }
+            Chunk::Samples(c) => {
+                // the sample_map maps stream IDs to a vector of iterators which each iterate over one chunk's samples
+                // so we can merge them later
+                stream_chunks
+                    .sample_map
+                    .entry(c.stream_id)
+                    .or_default()
+                    .push(c.samples.into_iter());
+            }
+            Chunk::ClockOffset(c) if !(c.collection_time.is_finite() && c.offset_value.is_finite()) => {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::NonFiniteClockOffset,
+                    Some(c.stream_id),
+                    Some(offset),
+                ));
+                if options.drop_non_finite_clock_offsets {
+                    *stream_chunks.rejected_clock_offsets.entry(c.stream_id).or_default() += 1;
+                } else {
+                    stream_chunks.clock_offsets.entry(c.stream_id).or_default().push(c);
+                }
+            }
+            Chunk::ClockOffset(c) => stream_chunks.clock_offsets.entry(c.stream_id).or_default().push(c),
+            // boundary chunks are discarded for now, only the first file header is used
+            // and chunks with unknown tags are kept separately while reading
+            Chunk::Boundary(_) | Chunk::FileHeader(_) | Chunk::Unknown(_) => (),
+        }
+    }
+}
+
+impl StreamChunks {
+    // creates a footer from the samples and clock offsets of every stream without one, like the recorder would have written it.
+    // Returns the IDs of those streams.
+    fn synthesize_missing_footers(&mut self) -> Vec<StreamID> {
+        let mut synthesized = Vec::new();
+
+        for stream_header in &self.stream_header_chunks {
+            let stream_id = stream_header.stream_id;
+            if self.stream_footer_chunks.iter().any(|f| f.stream_id == stream_id) {
+                continue;
+            }
+
+            let mut footer_stats = FooterStats::default();
+            for samples in self.sample_map.get(&stream_id).into_iter().flatten() {
+                footer_stats.add_samples(samples.as_slice());
+            }
+            for offset in self.clock_offsets.get(&stream_id).into_iter().flatten() {
+                footer_stats.add_clock_offset(offset.collection_time, offset.offset_value);
+            }
+
+            let xml = footer_stats.footer(stream_header.info.nominal_srate);
+            self.stream_footer_chunks.push(StreamFooterChunk {
+                stream_id,
+                info: parsers::stream_footer_info(&xml),
+                xml,
+            });
+            synthesized.push(stream_id);
+        }
+
+        synthesized
+    }
+}
+
+// the stream headers with their offsets, sorted by the offset so that the streams are always in the same order.
+// A later header of the same stream replaces the earlier one.
+fn stream_headers_in_file_order(
+    header_offsets: &[(StreamID, u64)],
+    stream_header_chunks: Vec<StreamHeaderChunk>,
+) -> Vec<(u64, StreamHeaderChunk)> {
+    let stream_header_map: HashMap<StreamID, (u64, StreamHeaderChunk)> = header_offsets
+        .iter()
+        .map(|&(_, offset)| offset)
+        .zip(stream_header_chunks)
+        .map(|(offset, s)| (s.stream_id, (offset, s)))
+        .collect();
+
+    let mut stream_headers: Vec<(u64, StreamHeaderChunk)> = stream_header_map.into_values().collect();
+    stream_headers.sort_by_key(|&(offset, _)| offset);
+    stream_headers
+}
+
+// takes grouped chunks and combines them into finished streams.
+// Their footers are checked on the way, as the timestamps before clock correction are not necessarily kept.
+fn process_streams(
+    mut grouped_chunks: StreamChunks,
+    options: &ParseOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<Vec<Stream>, XDFError> {
+    let stream_headers = stream_headers_in_file_order(
+        &grouped_chunks.header_offsets,
+        std::mem::take(&mut grouped_chunks.stream_header_chunks),
+    );
+
+    let mut stream_footer_map: HashMap<StreamID, StreamFooterChunk> = grouped_chunks
+        .stream_footer_chunks
+        .into_iter()
+        .map(|s| (s.stream_id, s))
+        .collect();
+
     let mut streams_vec: Vec<Stream> = Vec::new();
 
-    for (stream_id, stream_header) in stream_header_map {
+    for (header_offset, stream_header) in stream_headers {
+        let stream_id = stream_header.stream_id;
         let stream_footer = stream_footer_map.remove(&stream_id);
 
         let name = stream_header.info.name.as_ref().map(|name| Arc::from(name.as_str()));
@@ -261,49 +797,60 @@ fn process_streams(mut grouped_chunks: GroupedChunks) -> Result<Vec<Stream>, XDF
             .as_ref()
             .map(|stream_type| Arc::from(stream_type.as_str()));
 
-        let mut stream_offsets = grouped_chunks
+        // clock offsets which are not finite have already been dropped, unless they should be kept
+        let stream_offsets = grouped_chunks
             .clock_offsets
             .remove(&stream_header.stream_id)
             .unwrap_or_default();
 
-        // Since clock offsets are internal types only, I could look into usinng a FiniteF64 type.
-        stream_offsets.retain(|o| o.collection_time.is_finite() && o.offset_value.is_finite());
+        let clock_ranges = segments::clock_segments(&stream_offsets, &options.segmentation);
 
-        if !stream_offsets.is_sorted() {
+        // offsets which are not finite can't be compared, they are ignored when checking the order
+        if !clock_ranges.iter().all(|range| {
+            stream_offsets[range.clone()].is_sorted_by(|a, b| a.partial_cmp(b) != Some(std::cmp::Ordering::Greater))
+        }) {
             return Err(ParseError::InvalidClockOffset.into());
         }
-
-        let samples_vec: Vec<Sample> = process_samples(
+        let clock_bounds = segments::clock_segment_bounds(&stream_offsets, &clock_ranges);
+
+        let ProcessedSamples {
+            samples: mut samples_vec,
+            raw_timestamps,
+            interpolated_timestamps,
+            corrected_timestamps,
+            first_and_last_interpolated,
+            clock_segments,
+        } = process_samples(
             grouped_chunks.sample_map.remove(&stream_id).unwrap_or_default(),
             &stream_offsets,
+            &clock_ranges,
+            &clock_bounds,
             stream_header.info.nominal_srate,
+            options,
         );
 
-        let measured_srate = if stream_header.info.nominal_srate.is_some() {
-            // nominal_srate is given as "a floating point number in Hertz. If the stream
-            // has an irregular sampling rate (that is, the samples are not spaced evenly in
-            // time, for example in an event stream), this value must be 0."
-            // we use None instead of 0.
+        let segment_ranges = segments::find_segments(
+            &samples_vec,
+            &clock_segments,
+            stream_header.info.nominal_srate,
+            &options.segmentation,
+        );
+        if options.dejitter {
+            segments::dejitter(&mut samples_vec, &segment_ranges);
+        }
+        let segments = segments::segments(segment_ranges, &samples_vec);
 
-            let first_timestamp: Option<f64> = samples_vec.first().and_then(|s| s.timestamp);
-            let last_timestamp: Option<f64> = samples_vec.last().and_then(|s| s.timestamp);
+        let rejected = grouped_chunks
+            .rejected_clock_offsets
+            .remove(&stream_id)
+            .unwrap_or_default();
+        let clock_offset_stats = sync::offset_stats(&stream_offsets, &clock_ranges, rejected);
 
-            if let (Some(first_timestamp), Some(last_timestamp)) = (first_timestamp, last_timestamp) {
-                let delta = last_timestamp - first_timestamp;
-                if delta <= 0.0 || !delta.is_finite() {
-                    None // don't divide by zero :)
-                } else {
-                    Some(samples_vec.len() as f64 / delta)
-                }
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+        let measured_srate = measured_srate(&samples_vec, stream_header.info.nominal_srate);
 
         let stream = Stream {
             id: stream_id,
+            header_offset,
             channel_count: stream_header.info.channel_count,
             nominal_srate: stream_header.info.nominal_srate,
             format: stream_header.info.channel_format,
@@ -311,74 +858,92 @@ fn process_streams(mut grouped_chunks: GroupedChunks) -> Result<Vec<Stream>, XDF
             name,
             r#type: stream_type,
             header: stream_header.xml,
+            footer_info: stream_footer.as_ref().map(|s| s.info.clone()),
             footer: stream_footer.map(|s| s.xml),
             measured_srate,
+            clock_offsets: stream_offsets
+                .iter()
+                .map(|o| (o.collection_time, o.offset_value))
+                .collect(),
             samples: samples_vec,
+            raw_timestamps,
+            interpolated_timestamps,
+            corrected_timestamps,
+            segments,
+            clock_offset_stats,
         };
 
+        check_footer_contents(&stream, first_and_last_interpolated, diagnostics);
         streams_vec.push(stream);
     }
 
     Ok(streams_vec)
 }
 
+// the sampling rate calculated from the number of samples and the time between the first and last one
+fn measured_srate(samples: &[Sample], nominal_srate: Option<f64>) -> Option<f64> {
+    // nominal_srate is given as "a floating point number in Hertz. If the stream
+    // has an irregular sampling rate (that is, the samples are not spaced evenly in
+    // time, for example in an event stream), this value must be 0."
+    // we use None instead of 0.
+    nominal_srate?;
+
+    let first_timestamp: Option<f64> = samples.first().and_then(|s| s.timestamp);
+    let last_timestamp: Option<f64> = samples.last().and_then(|s| s.timestamp);
+
+    if let (Some(first_timestamp), Some(last_timestamp)) = (first_timestamp, last_timestamp) {
+        let delta = last_timestamp - first_timestamp;
+        if delta <= 0.0 || !delta.is_finite() {
+            None // don't divide by zero :)
+        } else {
+            Some(samples.len() as f64 / delta)
+        }
+    } else {
+        None
+    }
+}
+
+// the samples of a stream along with their timestamps after each processing step, if they are kept
+struct ProcessedSamples {
+    samples: Vec<Sample>,
+    raw_timestamps: Vec<Option<f64>>,
+    interpolated_timestamps: Vec<Option<f64>>,
+    corrected_timestamps: Vec<Option<f64>>,
+    // the interpolated timestamps of the first and the last sample, which are always kept
+    first_and_last_interpolated: (Option<f64>, Option<f64>),
+    // the index of the part of the clock offsets each sample was corrected with
+    clock_segments: Vec<Option<usize>>,
+}
+
 /// takes a bunch of iterators over a stream's samples and some offsets and
 /// combines them into a vector of samples with timestamps corrected by interpolated clock offsets.
+/// Which of these steps are taken is decided by the options.
 fn process_samples(
-    mut sample_iterators: Vec<SampleIter>,
+    sample_iterators: Vec<SampleIter>,
     stream_offsets: &[ClockOffsetChunk],
+    clock_ranges: &[Range<usize>],
+    clock_bounds: &[(f64, f64)],
     nominal_srate: Option<f64>,
-) -> Vec<Sample> {
+    options: &ParseOptions,
+) -> ProcessedSamples {
     debug_assert!(stream_offsets
         .iter()
         .all(|o| o.stream_id == stream_offsets[0].stream_id));
 
-    let mut offset_index: usize = 0;
+    // the offsets are split at clock resets, each part is used on its own
+    let mut offset_indices: Vec<usize> = vec![0; clock_ranges.len()];
+    let fits: Vec<_> = clock_ranges
+        .iter()
+        .map(|range| sync::fit(options.clock_sync, &stream_offsets[range.clone()]))
+        .collect();
 
     let mut most_recent_timestamp = (0_usize, 0_f64);
 
-    // Sort the iterators according to first timestamp.
-    // If the first sample from this iterator has no timestamp, append this iterator to the previous iterator
-    // What if the first sample from the first iterator also has no timestamp?
-    // Both the Python and the Matlab implementations use zero as a first default, so I've done the same here.
-
-    let mut sample_iterators_merged = vec![];
-    if let Some((first, rest)) = sample_iterators.split_first_mut() {
-        // We store each set of iterators with the first iter's first timestamp in a tuple
-        let mut first = first.peekable();
-        let first_ts = first
-            .peek()
-            .and_then(|s| s.timestamp)
-            .and_then(FiniteF64::new)
-            .unwrap_or(FiniteF64::zero());
-        sample_iterators_merged.push((first_ts, vec![first]));
-
-        for it in rest {
-            let mut it = it.peekable();
-            if let Some(first_sample) = it.peek() {
-                // If there is a timestamp and it is finite, create a new set of iterators
-                if let Some(ts) = first_sample.timestamp.and_then(FiniteF64::new) {
-                    sample_iterators_merged.push((ts, vec![it]));
-                } else {
-                    // Technically this need not be checked as there is always a last
-                    if let Some(v) = sample_iterators_merged.last_mut() {
-                        v.1.push(it);
-                    }
-                }
-            }
-        }
-    }
-
-    // Now we have a vec of tuples containing a finite timestamp and a vec of iterators.
-    // We need to sort the outer vec and chain the iterators in each inner vec.
-
-    sample_iterators_merged.sort_by_key(|t| t.0);
-
-    let sample_iterators = sample_iterators_merged
-        .into_iter()
-        .flat_map(|t| t.1)
-        .map(Iterator::peekable)
-        .filter_map(|mut it| if it.peek().is_none() { None } else { Some(it) });
+    let sample_iterators = if options.sort_chunks {
+        sort_chunks(sample_iterators)
+    } else {
+        sample_iterators
+    };
 
     // let samples_in_order: bool = sample_iterators
     //     .clone()
@@ -389,34 +954,104 @@ fn process_samples(
     //     return Err(XDFError::InvalidSample);
     // }
 
-    sample_iterators
-        .into_iter()
-        .flatten()
-        .enumerate()
-        .map(|(i, s)| -> Sample {
-            if let Some(srate) = nominal_srate {
-                let timestamp = if let Some(timestamp) = s.timestamp {
-                    // if the sample has its own timestamp, use that and update the most recent timestamp
-                    most_recent_timestamp = (i, timestamp);
-                    s.timestamp
-                } else {
-                    // if this sample has no timestamp but a previous sample did, calculate this one's timestamp using the srate
-                    let (old_i, old_timestamp) = most_recent_timestamp;
-                    let samples_since_ts = i - old_i;
-                    Some(old_timestamp + (samples_since_ts as f64 / srate))
-                };
+    let num_samples = sample_iterators.iter().map(ExactSizeIterator::len).sum();
+    let num_columns = if options.timestamp_columns { num_samples } else { 0 };
+    let mut processed = ProcessedSamples {
+        samples: Vec::with_capacity(num_samples),
+        raw_timestamps: Vec::with_capacity(num_columns),
+        interpolated_timestamps: Vec::with_capacity(num_columns),
+        corrected_timestamps: Vec::with_capacity(num_columns),
+        first_and_last_interpolated: (None, None),
+        clock_segments: Vec::with_capacity(num_samples),
+    };
 
-                let timestamp = timestamp.map(|ts| interpolate_and_add_offsets(ts, stream_offsets, &mut offset_index));
+    for (i, s) in sample_iterators.into_iter().flatten().enumerate() {
+        let (interpolated, corrected, clock_segment) = if let Some(srate) = nominal_srate {
+            let interpolated = if let Some(timestamp) = s.timestamp {
+                // if the sample has its own timestamp, use that and update the most recent timestamp
+                most_recent_timestamp = (i, timestamp);
+                s.timestamp
+            } else if options.interpolate_timestamps {
+                // if this sample has no timestamp but a previous sample did, calculate this one's timestamp using the srate
+                let (old_i, old_timestamp) = most_recent_timestamp;
+                let samples_since_ts = i - old_i;
+                Some(old_timestamp + (samples_since_ts as f64 / srate))
+            } else {
+                None
+            };
 
-                Sample {
-                    timestamp,
-                    values: s.values,
+            match interpolated {
+                Some(ts) if options.clock_correction && !clock_ranges.is_empty() => {
+                    // without clock resets there is only a single part to choose from
+                    let k = if clock_bounds.len() == 1 {
+                        0
+                    } else {
+                        segments::clock_segment_for(ts, clock_bounds)
+                    };
+                    let corrected = match fits[k] {
+                        Some(fit) => ts + fit.value_at(ts),
+                        None => interpolate_and_add_offsets(
+                            ts,
+                            &stream_offsets[clock_ranges[k].clone()],
+                            &mut offset_indices[k],
+                        ),
+                    };
+                    (interpolated, Some(corrected), Some(k))
                 }
-            } else {
-                s
+                _ => (interpolated, interpolated, None),
             }
-        })
-        .collect()
+        } else {
+            (s.timestamp, s.timestamp, None)
+        };
+
+        if i == 0 {
+            processed.first_and_last_interpolated.0 = interpolated;
+        }
+        processed.first_and_last_interpolated.1 = interpolated;
+        if options.timestamp_columns {
+            processed.raw_timestamps.push(s.timestamp);
+            processed.interpolated_timestamps.push(interpolated);
+            processed.corrected_timestamps.push(corrected);
+        }
+        processed.clock_segments.push(clock_segment);
+        processed.samples.push(Sample {
+            timestamp: corrected,
+            values: s.values,
+        });
+    }
+
+    processed
+}
+
+/// sorts the iterators over the chunks of a stream according to the timestamp of their first sample.
+fn sort_chunks(sample_iterators: Vec<SampleIter>) -> Vec<SampleIter> {
+    // If the first sample from this iterator has no timestamp, append this iterator to the previous iterator
+    // What if the first sample from the first iterator also has no timestamp?
+    // Both the Python and the Matlab implementations use zero as a first default, so I've done the same here.
+
+    // We store each set of iterators with the first iter's first timestamp in a tuple
+    let mut sample_iterators_merged: Vec<(FiniteF64, Vec<SampleIter>)> = vec![];
+    for it in sample_iterators {
+        let Some(first_sample) = it.as_slice().first() else {
+            continue;
+        };
+
+        // If there is a timestamp and it is finite, create a new set of iterators
+        match (
+            first_sample.timestamp.and_then(FiniteF64::new),
+            sample_iterators_merged.last_mut(),
+        ) {
+            (None, Some(v)) => v.1.push(it),
+            (ts, _) => sample_iterators_merged.push((ts.unwrap_or(FiniteF64::zero()), vec![it])),
+        }
+    }
+
+    // Now we have a vec of tuples containing a finite timestamp and a vec of iterators.
+    // We need to sort the outer vec and chain the iterators in each inner vec.
+
+    sample_iterators_merged.sort_by_key(|t| t.0);
+
+    sample_iterators_merged.into_iter().flat_map(|t| t.1).collect()
 }
 
 /// takes a timestamp and a vector of clock offsets and interpolates the offsets to find an offset for the timestamp.
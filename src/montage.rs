@@ -0,0 +1,266 @@
+use std::fmt::Write;
+
+use xmltree::Element;
+
+use crate::ChannelInfo;
+
+// electrode locations as described by the XDF meta-data conventions for EEG:
+// <desc>
+//     <channels>
+//         <channel>
+//             <label>C3</label>
+//             <location><X>-65.6</X><Y>-6.1</Y><Z>71.4</Z></location>
+//         </channel>
+//         ...
+//     </channels>
+//     <cap><name>...</name><size>...</size><manufacturer>...</manufacturer><labelscheme>...</labelscheme></cap>
+// </desc>
+
+/// The position of a single electrode, see [`Montage`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElectrodePosition {
+    /// The label of the channel the electrode belongs to.
+    pub label: String,
+    /// The type of the channel, for example "EEG" or "EOG", if given.
+    pub r#type: Option<String>,
+    /// Towards the right ear, in millimeters.
+    pub x: f64,
+    /// Towards the nasion, in millimeters.
+    pub y: f64,
+    /// Towards the vertex, in millimeters.
+    pub z: f64,
+}
+
+/// The EEG cap the electrodes are mounted on, as given in `desc/cap` of the stream header.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CapInfo {
+    /// The name of the cap, if given.
+    pub name: Option<String>,
+    /// The size of the cap, for example "56", if given.
+    pub size: Option<String>,
+    /// The manufacturer of the cap, if given.
+    pub manufacturer: Option<String>,
+    /// The labeling scheme of the electrodes, for example "10-20", if given.
+    pub label_scheme: Option<String>,
+}
+
+/**
+The electrode positions of an EEG stream, see [`crate::Stream::montage`].
+
+Following the XDF meta-data conventions, positions are given in millimeters ([`Montage::UNIT`]) in a head coordinate
+frame ([`Montage::COORDINATE_FRAME`]) whose origin is the center of the head, with X pointing towards the right ear,
+Y towards the nasion and Z towards the vertex.
+The exports convert them to the conventions of the respective format.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Montage {
+    /// The positions of the electrodes, in the order of the channels. Channels without a complete location are left out.
+    pub positions: Vec<ElectrodePosition>,
+    /// The cap the electrodes are mounted on, if the header describes it.
+    pub cap: Option<CapInfo>,
+}
+
+impl Montage {
+    /// The unit of the positions.
+    pub const UNIT: &'static str = "mm";
+    /// The coordinate frame of the positions: X right, Y anterior and Z superior.
+    pub const COORDINATE_FRAME: &'static str = "RAS";
+
+    /**
+    Export the montage in the BESA `.elp` format, with one electrode per line given by its type, label and spherical
+    angles in degrees. Theta is the angle from the vertex, negative on the left hemisphere, and phi is the angle from
+    the right ear (or the left ear on the left hemisphere) towards the nasion.
+    */
+    #[must_use]
+    pub fn to_elp(&self) -> String {
+        let mut elp = String::new();
+        for position in &self.positions {
+            let (inclination, azimuth) = spherical_angles(position.x, position.y, position.z);
+            // BESA mirrors the angles of the left hemisphere so that phi stays within -90 to 90 degrees
+            let (theta, phi) = if position.x < 0.0 {
+                let phi = if azimuth > 0.0 {
+                    azimuth - 180.0
+                } else {
+                    azimuth + 180.0
+                };
+                (-inclination, phi)
+            } else {
+                (inclination, azimuth)
+            };
+            let channel_type = position.r#type.as_deref().unwrap_or("EEG");
+            // writing to a String can't fail
+            let _ = writeln!(elp, "{channel_type}\t{}\t{theta:.4}\t{phi:.4}", position.label);
+        }
+        elp
+    }
+
+    /**
+    Export the montage in the EEGLAB `.ced` format, a tab-separated table with a header line.
+    EEGLAB's X axis points towards the nasion and its Y axis towards the left ear, so the coordinates are rotated
+    accordingly. The polar and spherical coordinates are derived from them like EEGLAB's `convertlocs` does.
+    */
+    #[must_use]
+    pub fn to_ced(&self) -> String {
+        let mut ced = String::from("Number\tlabels\ttheta\tradius\tX\tY\tZ\tsph_theta\tsph_phi\tsph_radius\ttype\n");
+        for (i, position) in self.positions.iter().enumerate() {
+            // subtracting from zero avoids printing "-0.0000" for electrodes on the midline
+            let (x, y, z) = (position.y, 0.0 - position.x, position.z);
+            let sph_radius = (x * x + y * y + z * z).sqrt();
+            let sph_theta = y.atan2(x).to_degrees();
+            let sph_phi = z.atan2(x.hypot(y)).to_degrees();
+            let theta = 0.0 - sph_theta;
+            let radius = 0.5 - sph_phi / 180.0;
+            let _ = writeln!(
+                ced,
+                "{}\t{}\t{theta:.4}\t{radius:.4}\t{x:.4}\t{y:.4}\t{z:.4}\t{sph_theta:.4}\t{sph_phi:.4}\t{sph_radius:.4}\t{}",
+                i + 1,
+                position.label,
+                position.r#type.as_deref().unwrap_or(""),
+            );
+        }
+        ced
+    }
+
+    /**
+    Export the montage as a tab-separated table with a header line and the name and X, Y and Z coordinates of every
+    electrode, like the `electrodes.tsv` files of BIDS. The coordinates are in millimeters in the montage's coordinate frame.
+    */
+    #[must_use]
+    pub fn to_tsv(&self) -> String {
+        let mut tsv = String::from("name\tx\ty\tz\n");
+        for position in &self.positions {
+            let _ = writeln!(
+                tsv,
+                "{}\t{}\t{}\t{}",
+                position.label, position.x, position.y, position.z
+            );
+        }
+        tsv
+    }
+}
+
+// the angle from the Z axis and the angle in the X-Y plane from the X axis towards the Y axis, in degrees
+fn spherical_angles(x: f64, y: f64, z: f64) -> (f64, f64) {
+    let inclination = x.hypot(y).atan2(z).to_degrees();
+    let azimuth = y.atan2(x).to_degrees();
+    (inclination, azimuth)
+}
+
+fn position(channel: &ChannelInfo) -> Option<ElectrodePosition> {
+    let location = channel.extra.iter().find(|e| e.name == "location")?;
+    let coordinate = |name: &str| location.get_child(name)?.get_text()?.trim().parse::<f64>().ok();
+
+    Some(ElectrodePosition {
+        label: channel.label.clone(),
+        r#type: channel.r#type.clone(),
+        x: coordinate("X")?,
+        y: coordinate("Y")?,
+        z: coordinate("Z")?,
+    })
+}
+
+fn cap(header: &Element) -> Option<CapInfo> {
+    let cap = header.get_child("desc")?.get_child("cap")?;
+    let text = |name: &str| {
+        cap.get_child(name)
+            .and_then(Element::get_text)
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+    };
+
+    Some(CapInfo {
+        name: text("name"),
+        size: text("size"),
+        manufacturer: text("manufacturer"),
+        label_scheme: text("labelscheme"),
+    })
+}
+
+// the montage of the channels, or None if none of them has a location
+pub(crate) fn montage(header: &Element, channels: &[ChannelInfo]) -> Option<Montage> {
+    let positions: Vec<ElectrodePosition> = channels.iter().filter_map(position).collect();
+    if positions.is_empty() {
+        return None;
+    }
+
+    Some(Montage {
+        positions,
+        cap: cap(header),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channels::channels;
+
+    fn header() -> Element {
+        Element::parse(
+            "<info><desc>
+                <channels>
+                    <channel><label>T8</label><location><X>80</X><Y>0</Y><Z>0</Z></location></channel>
+                    <channel><label>Fp1</label><type>EEG</type><location><X>-30</X><Y>90</Y><Z>0</Z></location></channel>
+                    <channel><label>Cz</label><location><X>0</X><Y>0</Y><Z>95</Z></location></channel>
+                    <channel><label>AUX</label></channel>
+                </channels>
+                <cap><name>actiCAP</name><size>58</size><labelscheme>10-20</labelscheme></cap>
+            </desc></info>"
+                .as_bytes(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_montage() {
+        let header = header();
+        let montage = montage(&header, &channels(&header, 4)).unwrap();
+
+        let labels: Vec<&str> = montage.positions.iter().map(|p| p.label.as_str()).collect();
+        assert_eq!(labels, ["T8", "Fp1", "Cz"]);
+        assert_eq!(
+            montage.positions[1],
+            ElectrodePosition {
+                label: "Fp1".to_string(),
+                r#type: Some("EEG".to_string()),
+                x: -30.0,
+                y: 90.0,
+                z: 0.0,
+            }
+        );
+
+        let cap = montage.cap.unwrap();
+        assert_eq!(cap.name.as_deref(), Some("actiCAP"));
+        assert_eq!(cap.size.as_deref(), Some("58"));
+        assert_eq!(cap.manufacturer, None);
+        assert_eq!(cap.label_scheme.as_deref(), Some("10-20"));
+
+        let without_locations = Element::parse("<info><desc/></info>".as_bytes()).unwrap();
+        assert!(super::montage(&without_locations, &channels(&without_locations, 2)).is_none());
+    }
+
+    #[test]
+    fn test_exports() {
+        let header = header();
+        let montage = montage(&header, &channels(&header, 4)).unwrap();
+
+        let elp = montage.to_elp();
+        let lines: Vec<&str> = elp.lines().collect();
+        assert_eq!(lines[0], "EEG\tT8\t90.0000\t0.0000");
+        assert_eq!(lines[1], "EEG\tFp1\t-90.0000\t-71.5651");
+        assert_eq!(lines[2], "EEG\tCz\t0.0000\t0.0000");
+
+        let ced = montage.to_ced();
+        let lines: Vec<&str> = ced.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[1],
+            "1\tT8\t90.0000\t0.5000\t0.0000\t-80.0000\t0.0000\t-90.0000\t0.0000\t80.0000\t"
+        );
+        assert!(lines[3].starts_with("3\tCz\t0.0000\t0.0000\t0.0000\t0.0000\t95.0000"));
+
+        assert_eq!(
+            montage.to_tsv(),
+            "name\tx\ty\tz\nT8\t80\t0\t0\nFp1\t-30\t90\t0\nCz\t0\t0\t95\n"
+        );
+    }
+}
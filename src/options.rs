@@ -0,0 +1,373 @@
+use std::{fmt, sync::Arc};
+
+use crate::StreamHeaderChunk;
+
+type StreamPredicate = Arc<dyn Fn(&StreamHeaderChunk) -> bool + Send + Sync>;
+
+/**
+Options for loading an XDF file with [`crate::XDFFile::from_bytes_with`] or [`crate::XDFFile::from_reader_with`].
+
+The default options load every stream, just like [`crate::XDFFile::from_bytes`].
+
+# Example
+```rust
+# use std::fs;
+# use xdf::{ParseOptions, XDFFile};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let bytes = fs::read("tests/minimal.xdf")?;
+let options = ParseOptions::new().select_streams(|header| header.info.stream_type.as_deref() == Some("EEG"));
+let xdf_file = XDFFile::from_bytes_with(&bytes, &options)?;
+assert_eq!(xdf_file.streams.len(), 1);
+# Ok(())
+# }
+```
+*/
+// every switch is independent, so an enum or state machine would not make this any clearer
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone)]
+pub struct ParseOptions {
+    pub(crate) stream_predicate: Option<StreamPredicate>,
+    pub(crate) recover: bool,
+    pub(crate) lenient: bool,
+    pub(crate) limits: ParseLimits,
+    pub(crate) allow_unknown_versions: bool,
+    pub(crate) clock_correction: bool,
+    pub(crate) clock_sync: ClockSync,
+    pub(crate) interpolate_timestamps: bool,
+    pub(crate) sort_chunks: bool,
+    pub(crate) drop_non_finite_clock_offsets: bool,
+    pub(crate) dejitter: bool,
+    pub(crate) timestamp_columns: bool,
+    pub(crate) segmentation: Segmentation,
+}
+
+/**
+Limits on what a file may contain, to safely parse files from untrusted sources.
+
+Lengths and counts in a file are trusted by default, so a small malicious file could make the parser allocate huge
+amounts of memory. Exceeding a limit fails parsing with [`crate::ParseErrorReason::LimitExceeded`].
+The default limits allow everything.
+
+# Example
+```rust
+# use std::fs;
+# use xdf::{ParseLimits, ParseOptions, XDFFile};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let limits = ParseLimits {
+    max_chunk_size: 1 << 20,
+    max_decoded_bytes: 1 << 30,
+    ..ParseLimits::default()
+};
+let bytes = fs::read("tests/minimal.xdf")?;
+let xdf_file = XDFFile::from_bytes_with(&bytes, &ParseOptions::new().limits(limits))?;
+# Ok(())
+# }
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// The maximum length of a chunk in bytes, as given by the length at its start. Checked before the chunk is read.
+    pub max_chunk_size: u64,
+    /// The maximum number of samples in a single samples chunk.
+    pub max_samples_per_chunk: u64,
+    /// The maximum channel count of a stream.
+    pub max_channels: u32,
+    /// The maximum length of a single string value in bytes.
+    pub max_string_length: u64,
+    /// The maximum size in memory of all decoded samples together, in bytes.
+    /// A samples chunk is checked against it before decoding, using the number of samples and channels in its header.
+    /// The timestamps kept by [`ParseOptions::timestamp_columns`] are included.
+    pub max_decoded_bytes: u64,
+}
+
+/**
+How the clock offsets of a stream are used to correct its timestamps, see [`ParseOptions::clock_sync`].
+
+# Example
+```rust
+# use std::fs;
+# use xdf::{ClockSync, ParseOptions, XDFFile};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let bytes = fs::read("tests/minimal.xdf")?;
+let options = ParseOptions::new().clock_sync(ClockSync::Robust { winsor_threshold: 1E-4 });
+let xdf_file = XDFFile::from_bytes_with(&bytes, &options)?;
+# Ok(())
+# }
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ClockSync {
+    /// Linearly interpolate between consecutive clock offsets. Every measurement is applied as is, including its jitter.
+    #[default]
+    Interpolate,
+    /// Fit a line to the clock offsets over their collection time using ordinary least squares.
+    LeastSquares,
+    /**
+    Fit a line to the clock offsets over their collection time, minimizing the Huber loss to be robust against outliers.
+    This is what pyxdf's `synchronize_clocks` does by default.
+    */
+    Robust {
+        /// Residuals larger than this many seconds are treated as outliers. pyxdf uses 0.0001.
+        /// A threshold which is not positive falls back to [`ClockSync::LeastSquares`].
+        winsor_threshold: f64,
+    },
+}
+
+/**
+Where streams are split into [`crate::Stream::segments`], see [`ParseOptions::segmentation`].
+
+A regularly sampled stream is split wherever there is a gap between two samples. With
+[`Segmentation::detect_clock_resets`], it is also split wherever the clock was reset, for example because the recording
+computer was restarted. Clock offsets are split at clock resets as well, and each part is only used to correct the
+timestamps of the samples recorded at that time.
+
+# Example
+```rust
+# use std::fs;
+# use xdf::{ParseOptions, Segmentation, XDFFile};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let segmentation = Segmentation {
+    max_gap_samples: 10.0,
+    min_gap_seconds: 0.0,
+    ..Segmentation::default()
+};
+let bytes = fs::read("tests/minimal.xdf")?;
+let xdf_file = XDFFile::from_bytes_with(&bytes, &ParseOptions::new().segmentation(segmentation))?;
+# Ok(())
+# }
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segmentation {
+    /// A gap is at least this many sample intervals (one over the nominal sampling rate) long. pyxdf uses 500.
+    pub max_gap_samples: f64,
+    /// A gap is also at least this many seconds long. pyxdf uses 1.
+    pub min_gap_seconds: f64,
+    /**
+    Detect clock resets. The samples are split wherever their timestamps jump back, and the clock offsets wherever
+    their collection times jump back or their values jump by more than [`Segmentation::max_offset_jump`].
+    Disabled by default, so that clock offsets which are not in order of collection time fail parsing with
+    [`crate::ParseError::InvalidClockOffset`] as before.
+    */
+    pub detect_clock_resets: bool,
+    /// The largest change in seconds between two consecutive clock offsets which is not a clock reset. pyxdf uses 1.
+    pub max_offset_jump: f64,
+}
+
+impl Default for Segmentation {
+    fn default() -> Self {
+        Self {
+            max_gap_samples: 500.0,
+            min_gap_seconds: 1.0,
+            detect_clock_resets: false,
+            max_offset_jump: 1.0,
+        }
+    }
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_chunk_size: u64::MAX,
+            max_samples_per_chunk: u64::MAX,
+            max_channels: u32::MAX,
+            max_string_length: u64::MAX,
+            max_decoded_bytes: u64::MAX,
+        }
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            stream_predicate: None,
+            recover: false,
+            lenient: false,
+            limits: ParseLimits::default(),
+            allow_unknown_versions: false,
+            clock_correction: true,
+            clock_sync: ClockSync::default(),
+            interpolate_timestamps: true,
+            sort_chunks: true,
+            drop_non_finite_clock_offsets: true,
+            dejitter: false,
+            timestamp_columns: true,
+            segmentation: Segmentation::default(),
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Create the default options.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+    Only load the streams whose header matches the predicate.
+
+    The predicate is called once for every stream header and can look at the stream ID, the parsed name, type and
+    source ID or the whole XML header. Samples chunks of streams which are not selected are skipped using their length,
+    without decoding them.
+    */
+    #[must_use]
+    pub fn select_streams<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&StreamHeaderChunk) -> bool + Send + Sync + 'static,
+    {
+        self.stream_predicate = Some(Arc::new(predicate));
+        self
+    }
+
+    /**
+    Recover from corrupted chunks instead of stopping at the first one.
+
+    When a chunk fails to parse, the file is searched for the next boundary chunk and parsing continues after it.
+    The skipped byte ranges are listed in [`crate::XDFFile::skipped_ranges`]. See [`crate::ChunkReader::recover`].
+    File header chunks after the first one are ignored and reported with [`crate::DiagnosticKind::MultipleFileHeader`]
+    instead of failing with [`crate::StreamError::MultipleFileHeader`].
+    Exceeding one of the [`ParseOptions::limits`] is still an error, as are chunks which can't be the result of damage.
+    */
+    #[must_use]
+    pub const fn recover(mut self, recover: bool) -> Self {
+        self.recover = recover;
+        self
+    }
+
+    /**
+    Load as much as possible from a file which ends in the middle of a chunk, for example after a power loss during a recording.
+
+    The complete samples of a cut-off samples chunk are decoded instead of being dropped, and streams without a footer
+    get one synthesized from their samples and clock offsets, like the recorder would have written it.
+    What was lost is reported in [`crate::XDFFile::truncation`]. See [`crate::ChunkReader::decode_truncated`].
+    */
+    #[must_use]
+    pub const fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Limit what the file may contain, see [`ParseLimits`].
+    #[must_use]
+    pub const fn limits(mut self, limits: ParseLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /**
+    Load files with an XDF version other than 1.x, which fail with [`crate::StreamError::UnsupportedVersion`] by default.
+    Such files are read like version 1.x files and reported with [`crate::DiagnosticKind::UnsupportedVersion`].
+    */
+    #[must_use]
+    pub const fn allow_unknown_versions(mut self, allow_unknown_versions: bool) -> Self {
+        self.allow_unknown_versions = allow_unknown_versions;
+        self
+    }
+
+    /**
+    Correct the timestamps of the samples by the stream's clock offsets, linearly interpolated between them.
+    Enabled by default. Disable it to get the timestamps as they were recorded, for example to do your own synchronization.
+    */
+    #[must_use]
+    pub const fn clock_correction(mut self, clock_correction: bool) -> Self {
+        self.clock_correction = clock_correction;
+        self
+    }
+
+    /// Choose how the clock offsets are turned into a correction of the timestamps, see [`ClockSync`].
+    #[must_use]
+    pub const fn clock_sync(mut self, clock_sync: ClockSync) -> Self {
+        self.clock_sync = clock_sync;
+        self
+    }
+
+    /**
+    Calculate the timestamps of samples which were recorded without one from the previous timestamp and the stream's nominal sampling rate.
+    Enabled by default. When disabled, these samples keep a timestamp of `None`.
+    */
+    #[must_use]
+    pub const fn interpolate_timestamps(mut self, interpolate_timestamps: bool) -> Self {
+        self.interpolate_timestamps = interpolate_timestamps;
+        self
+    }
+
+    /**
+    Sort the samples chunks of a stream by the timestamp of their first sample.
+    Enabled by default. When disabled, the samples are kept in the order they appear in the file.
+    */
+    #[must_use]
+    pub const fn sort_chunks(mut self, sort_chunks: bool) -> Self {
+        self.sort_chunks = sort_chunks;
+        self
+    }
+
+    /**
+    Drop clock offsets whose collection time or value is not finite, so that they do not spoil the clock correction.
+    Enabled by default. Either way, every such offset is reported with [`crate::DiagnosticKind::NonFiniteClockOffset`].
+    */
+    #[must_use]
+    pub const fn drop_non_finite_clock_offsets(mut self, drop_non_finite_clock_offsets: bool) -> Self {
+        self.drop_non_finite_clock_offsets = drop_non_finite_clock_offsets;
+        self
+    }
+
+    /**
+    Remove the jitter from the timestamps of regularly sampled streams, like pyxdf's `dejitter_timestamps`.
+
+    The timestamps of each of the stream's [`crate::Stream::segments`] are replaced by evenly spaced ones, fitted to
+    them by linear regression after the clock correction. Disabled by default.
+    */
+    #[must_use]
+    pub const fn dejitter(mut self, dejitter: bool) -> Self {
+        self.dejitter = dejitter;
+        self
+    }
+
+    /**
+    Keep the timestamps of each processing step in [`crate::Stream::raw_timestamps`],
+    [`crate::Stream::interpolated_timestamps`] and [`crate::Stream::corrected_timestamps`]. Enabled by default.
+
+    Disabling this leaves them empty, which saves three timestamps per sample. They count towards
+    [`ParseLimits::max_decoded_bytes`] when enabled.
+    */
+    #[must_use]
+    pub const fn timestamp_columns(mut self, timestamp_columns: bool) -> Self {
+        self.timestamp_columns = timestamp_columns;
+        self
+    }
+
+    /// Choose where streams are split into [`crate::Stream::segments`], see [`Segmentation`].
+    #[must_use]
+    pub const fn segmentation(mut self, segmentation: Segmentation) -> Self {
+        self.segmentation = segmentation;
+        self
+    }
+
+    // whether the stream should be loaded
+    pub(crate) fn is_selected(&self, stream_header: &StreamHeaderChunk) -> bool {
+        self.stream_predicate
+            .as_ref()
+            .is_none_or(|predicate| predicate(stream_header))
+    }
+}
+
+impl fmt::Debug for ParseOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParseOptions")
+            .field("select_streams", &self.stream_predicate.is_some())
+            .field("recover", &self.recover)
+            .field("lenient", &self.lenient)
+            .field("limits", &self.limits)
+            .field("allow_unknown_versions", &self.allow_unknown_versions)
+            .field("clock_correction", &self.clock_correction)
+            .field("clock_sync", &self.clock_sync)
+            .field("interpolate_timestamps", &self.interpolate_timestamps)
+            .field("sort_chunks", &self.sort_chunks)
+            .field("drop_non_finite_clock_offsets", &self.drop_non_finite_clock_offsets)
+            .field("dejitter", &self.dejitter)
+            .field("timestamp_columns", &self.timestamp_columns)
+            .field("segmentation", &self.segmentation)
+            .finish()
+    }
+}
@@ -3,9 +3,9 @@
 // [1, 4, or 8] [...] [Tag number] [Arbitrary]
 // [1] [As coded in NumLengthBytes] [2] [Variable]
 
-use nom::{bytes::complete::take, error::context, IResult};
+use nom::{bytes::complete::take, error::context};
 
-use super::chunk_length::length;
+use super::{chunk_length::length, IResult};
 
 pub(crate) fn chunk_content(input: &[u8]) -> IResult<&[u8], &[u8]> {
     let (input, chunk_size) = context("chunk_content chunk_size", length)(input)?;
@@ -1,17 +1,19 @@
-use nom::{
-    bytes::complete::tag,
-    combinator::value,
-    number::complete::{le_u32, le_u64, le_u8},
-    IResult, Parser,
+use nom::number::complete::{le_u32, le_u64, le_u8};
+
+use crate::errors::ParseErrorReason;
+
+use super::{
+    error::{error, failure},
+    IResult,
 };
 
 // num length bytes parser
 fn num_length_bytes(input: &[u8]) -> IResult<&[u8], u8> {
-    let parse_1 = value(1, tag([1_u8]));
-    let parse_4 = value(4, tag([4_u8]));
-    let parse_8 = value(8, tag([8_u8]));
-
-    nom::branch::alt((parse_1, parse_4, parse_8)).parse(input)
+    let (rest, num_length_bytes) = le_u8(input)?;
+    match num_length_bytes {
+        1 | 4 | 8 => Ok((rest, num_length_bytes)),
+        n => Err(error(input, ParseErrorReason::InvalidNumLengthBytes(n))),
+    }
 }
 
 // length parser
@@ -28,19 +30,12 @@ pub(crate) fn length(input: &[u8]) -> IResult<&[u8], usize> {
             Ok((input, length as usize))
         }
         8 => {
-            let (input, length) = le_u64(input)?;
-            let length = usize::try_from(length).map_err(|_| {
-                nom::Err::Failure(nom::error::Error::new(
-                    input,
-                    nom::error::ErrorKind::LengthValue, // not how these errors should be used but nom is a bit of a pain here
-                ))
-            })?;
-            Ok((input, length))
+            let (rest, length) = le_u64(input)?;
+            let length =
+                usize::try_from(length).map_err(|_| failure(input, ParseErrorReason::LengthTooLarge(length)))?;
+            Ok((rest, length))
         }
-        _ => Err(nom::Err::Failure(nom::error::Error::new(
-            input,
-            nom::error::ErrorKind::LengthValue, // not how these errors should be used but nom is a bit of a pain here
-        ))),
+        n => Err(failure(input, ParseErrorReason::InvalidNumLengthBytes(n))),
     }
 }
 
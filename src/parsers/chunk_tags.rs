@@ -1,6 +1,8 @@
-use nom::{self, bytes::complete::tag, combinator::value, IResult, Parser};
+use nom::{self, bytes::complete::tag, combinator::value, Parser};
 
-use crate::chunk_structs::Tag;
+use crate::chunk_structs::ChunkKind;
+
+use super::IResult;
 
 //tags:
 // 1: FileHeader (one per file)
@@ -11,31 +13,31 @@ use crate::chunk_structs::Tag;
 // 6: StreamFooter (one per stream)
 
 // FileHeader tag parser
-pub(crate) fn file_header_tag(input: &[u8]) -> IResult<&[u8], Tag> {
-    value(Tag::FileHeader, tag([1, 0])).parse(input)
+pub(crate) fn file_header_tag(input: &[u8]) -> IResult<&[u8], ChunkKind> {
+    value(ChunkKind::FileHeader, tag([1, 0])).parse(input)
 }
 
 // StreamHeader tag parser
-pub(crate) fn stream_header_tag(input: &[u8]) -> IResult<&[u8], Tag> {
-    value(Tag::StreamHeader, tag([2, 0])).parse(input)
+pub(crate) fn stream_header_tag(input: &[u8]) -> IResult<&[u8], ChunkKind> {
+    value(ChunkKind::StreamHeader, tag([2, 0])).parse(input)
 }
 
 // Samples tag parser
-pub(crate) fn samples_tag(input: &[u8]) -> IResult<&[u8], Tag> {
-    value(Tag::Samples, tag([3, 0])).parse(input)
+pub(crate) fn samples_tag(input: &[u8]) -> IResult<&[u8], ChunkKind> {
+    value(ChunkKind::Samples, tag([3, 0])).parse(input)
 }
 
 // ClockOffset tag parser
-pub(crate) fn clock_offset_tag(input: &[u8]) -> IResult<&[u8], Tag> {
-    value(Tag::ClockOffset, tag([4, 0])).parse(input)
+pub(crate) fn clock_offset_tag(input: &[u8]) -> IResult<&[u8], ChunkKind> {
+    value(ChunkKind::ClockOffset, tag([4, 0])).parse(input)
 }
 
 // Boundary tag parser
-pub(crate) fn boundary_tag(input: &[u8]) -> IResult<&[u8], Tag> {
-    value(Tag::Boundary, tag([5, 0])).parse(input)
+pub(crate) fn boundary_tag(input: &[u8]) -> IResult<&[u8], ChunkKind> {
+    value(ChunkKind::Boundary, tag([5, 0])).parse(input)
 }
 
 // StreamFooter tag parser
-pub(crate) fn stream_footer_tag(input: &[u8]) -> IResult<&[u8], Tag> {
-    value(Tag::StreamFooter, tag([6, 0])).parse(input)
+pub(crate) fn stream_footer_tag(input: &[u8]) -> IResult<&[u8], ChunkKind> {
+    value(ChunkKind::StreamFooter, tag([6, 0])).parse(input)
 }
@@ -3,11 +3,11 @@
 // [Ordinal number] [Double in seconds] [Double in seconds]
 // [4] [8] [8]
 
-use nom::{error::context, number::complete::le_f64, IResult};
+use nom::{error::context, number::complete::le_f64};
 
 use crate::ClockOffsetChunk;
 
-use super::{chunk_content, chunk_tags::clock_offset_tag, stream_id};
+use super::{chunk_content, chunk_tags::clock_offset_tag, stream_id, IResult};
 
 pub(super) fn clock_offset(input: &[u8]) -> IResult<&[u8], ClockOffsetChunk> {
     let (input, chunk_content) = context("clock_offset chunk_content", chunk_content)(input)?;
@@ -0,0 +1,48 @@
+use nom::error::{ContextError, ErrorKind, ParseError};
+
+use crate::errors::{Limit, ParseErrorReason};
+
+// the error type of all parsers, keeping the input where parsing failed and why
+#[derive(Debug, PartialEq)]
+pub(crate) struct Error<I> {
+    pub(crate) input: I,
+    pub(crate) reason: ParseErrorReason,
+}
+
+pub(crate) type IResult<I, O> = nom::IResult<I, O, Error<I>>;
+
+// an error after which other parsers may still be tried
+pub(crate) const fn error<I>(input: I, reason: ParseErrorReason) -> nom::Err<Error<I>> {
+    nom::Err::Error(Error { input, reason })
+}
+
+// an error which stops parsing altogether
+pub(crate) const fn failure<I>(input: I, reason: ParseErrorReason) -> nom::Err<Error<I>> {
+    nom::Err::Failure(Error { input, reason })
+}
+
+// fails if the value from the file exceeds the limit
+pub(crate) fn check_limit<I>(input: I, limit: Limit, value: u64, max: u64) -> Result<(), nom::Err<Error<I>>> {
+    if value > max {
+        return Err(failure(input, ParseErrorReason::LimitExceeded { limit, value, max }));
+    }
+    Ok(())
+}
+
+impl<I> ParseError<I> for Error<I> {
+    // only nom's own parsers end up here, the rest construct their errors with a fitting reason
+    fn from_error_kind(input: I, kind: ErrorKind) -> Self {
+        let reason = match kind {
+            ErrorKind::Eof => ParseErrorReason::UnexpectedEnd,
+            _ => ParseErrorReason::UnexpectedBytes,
+        };
+        Self { input, reason }
+    }
+
+    // the innermost error is the most specific one, so it is kept
+    fn append(_input: I, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<I> ContextError<I> for Error<I> {}
@@ -4,16 +4,23 @@ mod chunk_tags;
 mod boundary;
 mod chunk_content;
 mod clock_offset;
+mod error;
 mod file_header;
 mod samples;
 mod stream_footer;
 mod stream_header;
 mod stream_id;
+mod unknown;
 mod values;
 mod xml;
 
 pub(crate) mod xdf_file;
 
+pub(crate) use error::{Error, IResult};
+pub(crate) use samples::truncated_samples;
+pub(crate) use stream_footer::stream_footer_info;
+pub(crate) use stream_header::stream_header_info;
+
 use boundary::boundary;
 use chunk_content::chunk_content;
 use clock_offset::clock_offset;
@@ -22,5 +29,6 @@ use samples::samples;
 use stream_footer::stream_footer;
 use stream_header::stream_header;
 use stream_id::stream_id;
+use unknown::unknown;
 use values::values;
 use xml::xml;
@@ -1,32 +1,34 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::collections::HashMap;
 
 use nom::{
-    combinator,
     error::context,
     multi,
     number::complete::{le_f64, u8},
-    IResult,
 };
 
 use crate::{
     chunk_structs::{SamplesChunk, StreamHeaderChunkInfo},
-    Format, Sample,
+    errors::{Limit, ParseErrorReason},
+    Format, ParseLimits, Sample,
 };
 
-use super::{chunk_content, chunk_length::length, chunk_tags::samples_tag, stream_id, values};
+use super::{
+    chunk_content,
+    chunk_length::length,
+    chunk_tags::samples_tag,
+    error::{check_limit, error, failure},
+    stream_id, values, IResult,
+};
 
 fn optional_timestamp(input: &[u8]) -> IResult<&[u8], Option<f64>> {
-    let (input, timestamp_bytes) = u8(input)?;
+    let (rest, timestamp_bytes) = u8(input)?;
     match timestamp_bytes {
-        0 => Ok((input, None)),
+        0 => Ok((rest, None)),
         8 => {
-            let (input, timestamp) = le_f64(input)?;
-            Ok((input, Some(timestamp)))
+            let (rest, timestamp) = le_f64(rest)?;
+            Ok((rest, Some(timestamp)))
         }
-        _ => Err(nom::Err::Failure(nom::error::Error::new(
-            input,
-            nom::error::ErrorKind::Char, // not how these errors should be used but nom is a bit of a pain here
-        ))),
+        n => Err(failure(input, ParseErrorReason::InvalidTimestampBytes(n))),
     }
 }
 
@@ -35,32 +37,71 @@ fn optional_timestamp(input: &[u8]) -> IResult<&[u8], Option<f64>> {
 // [0 or 8] [Double, in seconds] [Value as defined by format] ...
 // [1][8 if TimeStampBytes==8, 0 if TimeStampBytes==0] [[Variable]] ...
 
-fn sample(input: &[u8], num_channels: usize, format: Format) -> IResult<&[u8], Sample> {
+fn sample<'a>(input: &'a [u8], num_channels: usize, format: Format, limits: &ParseLimits) -> IResult<&'a [u8], Sample> {
     let (input, timestamp) = context("sample optional_timestamp", optional_timestamp)(input)?;
-    let (input, values) = context("sample values", |i| values(i, format, num_channels))(input)?;
+    let (input, values) = context("sample values", |i| {
+        values(i, format, num_channels, limits.max_string_length)
+    })(input)?;
 
     Ok((input, Sample { timestamp, values }))
 }
 
-#[allow(clippy::needless_pass_by_value)]
-pub(super) fn samples(
-    input: &[u8],
-    // stream_info: &HashMap<u32, StreamHeaderChunkInfo>,
-    stream_info: Rc<RefCell<HashMap<u32, StreamHeaderChunkInfo>>>,
-) -> IResult<&[u8], SamplesChunk> {
-    let stream_info = stream_info.borrow();
+pub(super) fn samples<'a>(
+    input: &'a [u8],
+    stream_info: &HashMap<u32, StreamHeaderChunkInfo>,
+    limits: &ParseLimits,
+) -> IResult<&'a [u8], SamplesChunk> {
     let (input, chunk_content) = context("samples chunk_content", chunk_content)(input)?;
     let (chunk_content, _tag) = context("samples tag", samples_tag)(chunk_content)?; // 2 bytes
+    let stream_id_input = chunk_content;
     let (chunk_content, stream_id) = context("samples stream_id", stream_id)(chunk_content)?; // 4 bytes
+    let num_samples_input = chunk_content;
     let (chunk_content, num_samples) = context("samples num_samples", length)(chunk_content)?;
+    check_limit(
+        num_samples_input,
+        Limit::SamplesPerChunk,
+        num_samples as u64,
+        limits.max_samples_per_chunk,
+    )?;
 
     let Some(stream_info) = stream_info.get(&stream_id) else {
-        return context("samples get(&stream_id), missing a stream header", combinator::fail)(&[0]);
+        return Err(error(stream_id_input, ParseErrorReason::UnknownStream(stream_id)));
     };
     let num_channels = stream_info.channel_count as usize;
     let format = stream_info.channel_format;
 
-    let (_chunk_content, samples) = multi::count(|i| sample(i, num_channels, format), num_samples)(chunk_content)?;
+    let (_chunk_content, samples) =
+        multi::count(|i| sample(i, num_channels, format, limits), num_samples)(chunk_content)?;
 
     Ok((input, SamplesChunk { stream_id, samples }))
 }
+
+// parses the complete samples at the start of a samples chunk which is cut off by the end of the input.
+// The input is the chunk content starting at the tag, which is shorter than the chunk length says.
+// Returns the chunk with the samples that were complete and the number of samples it should have contained.
+pub(crate) fn truncated_samples<'a>(
+    input: &'a [u8],
+    stream_info: &HashMap<u32, StreamHeaderChunkInfo>,
+    limits: &ParseLimits,
+) -> IResult<&'a [u8], (SamplesChunk, usize)> {
+    let (input, _tag) = context("truncated samples tag", samples_tag)(input)?;
+    let (input, stream_id) = context("truncated samples stream_id", stream_id)(input)?;
+    let (mut input, num_samples) = context("truncated samples num_samples", length)(input)?;
+
+    let Some(stream_info) = stream_info.get(&stream_id) else {
+        return Err(error(input, ParseErrorReason::UnknownStream(stream_id)));
+    };
+    let num_channels = stream_info.channel_count as usize;
+    let format = stream_info.channel_format;
+
+    let mut samples = Vec::new();
+    while samples.len() < num_samples {
+        let Ok((rest, sample)) = sample(input, num_channels, format, limits) else {
+            break;
+        };
+        samples.push(sample);
+        input = rest;
+    }
+
+    Ok((input, (SamplesChunk { stream_id, samples }, num_samples)))
+}
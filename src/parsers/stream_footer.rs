@@ -1,14 +1,37 @@
-use nom::{error::context, IResult};
+use nom::error::context;
+use xmltree::Element;
 
-use crate::StreamFooterChunk;
+use crate::{util::get_text_from_child, StreamFooterChunk, StreamFooterChunkInfo};
 
-use super::{chunk_content, chunk_tags::stream_footer_tag, stream_id, xml};
+use super::{chunk_content, chunk_tags::stream_footer_tag, stream_id, xml, IResult};
 
 // stream footer structure
 // [StreamID] [XML UTF8 string]
 // [Ordinal number] [[Valid XML]]
 // [4] [As determined by chunk length]
 
+// extracts the info from the XML of a stream footer, ignoring fields which are missing or can't be parsed.
+pub(crate) fn stream_footer_info(xml: &Element) -> StreamFooterChunkInfo {
+    let number = |element: &Element, name: &str| get_text_from_child(element, name).ok()?.trim().parse::<f64>().ok();
+
+    let clock_offsets = xml
+        .get_child("clock_offsets")
+        .into_iter()
+        .flat_map(|offsets| offsets.children.iter().filter_map(|node| node.as_element()))
+        .filter(|offset| offset.name == "offset")
+        .filter_map(|offset| Some((number(offset, "time")?, number(offset, "value")?)))
+        .collect();
+
+    StreamFooterChunkInfo {
+        first_timestamp: number(xml, "first_timestamp"),
+        last_timestamp: number(xml, "last_timestamp"),
+        sample_count: get_text_from_child(xml, "sample_count")
+            .ok()
+            .and_then(|count| count.trim().parse().ok()),
+        clock_offsets,
+    }
+}
+
 pub(crate) fn stream_footer(input: &[u8]) -> IResult<&[u8], StreamFooterChunk> {
     let (input, chunk_content) = context("stream_footer chunk_content", chunk_content)(input)?;
 
@@ -16,5 +39,7 @@ pub(crate) fn stream_footer(input: &[u8]) -> IResult<&[u8], StreamFooterChunk> {
     let (chunk_content, stream_id) = context("stream_footer stream_id", stream_id)(chunk_content)?; // 4 bytes
     let (_chunk_content, xml) = context("stream_footer xml", |i| xml(i))(chunk_content)?;
 
-    Ok((input, StreamFooterChunk { stream_id, xml }))
+    let info = stream_footer_info(&xml);
+
+    Ok((input, StreamFooterChunk { stream_id, info, xml }))
 }
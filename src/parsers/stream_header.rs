@@ -1,12 +1,19 @@
-use nom::{error::context, IResult};
+use nom::error::context;
+use xmltree::Element;
 
 use crate::{
     chunk_structs::{StreamHeaderChunk, StreamHeaderChunkInfo},
+    errors::{Limit, ParseErrorReason},
     util::get_text_from_child,
-    Format,
+    Format, ParseLimits,
 };
 
-use super::{chunk_content, chunk_tags::stream_header_tag, stream_id, xml};
+use super::{
+    chunk_content,
+    chunk_tags::stream_header_tag,
+    error::{check_limit, failure},
+    stream_id, xml, IResult,
+};
 
 fn str_to_format(input: &str) -> Option<Format> {
     match input {
@@ -21,49 +28,59 @@ fn str_to_format(input: &str) -> Option<Format> {
     }
 }
 
-// StreamHeaderChunk contains streamID, info, and xml
-// the info contains channel count, nominal_srate, format, name, and type
-pub(crate) fn stream_header(input: &[u8]) -> IResult<&[u8], StreamHeaderChunk> {
-    let (input, chunk_content) = context("stream_header chunk_content", chunk_content)(input)?;
-
-    let (chunk_content, _) = context("stream_header tag", stream_header_tag)(chunk_content)?;
-    let (chunk_content, stream_id) = context("stream_header stream_id", stream_id)(chunk_content)?;
-    let (_chunk_content, xml) = context("stream_header xml", |i| xml(i))(chunk_content)?; // subtract 2 for the length field and 4 for the stream_id field
+// extracts the info from the XML of a stream header.
+// returns None if channel_count or channel_format are missing or invalid, or if nominal_srate is missing.
+pub(crate) fn stream_header_info(xml: &Element) -> Option<StreamHeaderChunkInfo> {
+    checked_stream_header_info(xml).ok()
+}
 
-    let text_results = (
-        get_text_from_child(&xml, "channel_count"),
-        get_text_from_child(&xml, "nominal_srate"),
-        get_text_from_child(&xml, "channel_format"),
-    );
+// like stream_header_info, but with the reason why the info is invalid
+fn checked_stream_header_info(xml: &Element) -> Result<StreamHeaderChunkInfo, ParseErrorReason> {
+    let text =
+        |name: &'static str| get_text_from_child(xml, name).map_err(|_| ParseErrorReason::InvalidStreamHeader(name));
 
-    let (Ok(channel_count_string), Ok(nominal_srate_string), Ok(format_string)) = text_results else {
-        return Err(nom::Err::Failure(nom::error::Error::new(
-            input,
-            nom::error::ErrorKind::Count, // not how these errors should be used but nom is a bit of a pain here
-        )));
-    };
+    let channel_count_string = text("channel_count")?;
+    let nominal_srate_string = text("nominal_srate")?;
+    let format_string = text("channel_format")?;
 
-    let (Some(channel_format), Some(channel_count)) =
-        (str_to_format(&format_string), channel_count_string.parse::<u32>().ok())
-    else {
-        return Err(nom::Err::Failure(nom::error::Error::new(
-            input,
-            nom::error::ErrorKind::Count, // not how these errors should be used but nom is a bit of a pain here
-        )));
-    };
+    let channel_format =
+        str_to_format(&format_string).ok_or_else(|| ParseErrorReason::UnsupportedFormat(format_string.clone()))?;
+    let channel_count = channel_count_string
+        .parse::<u32>()
+        .map_err(|_| ParseErrorReason::InvalidStreamHeader("channel_count"))?;
 
     let nominal_srate = nominal_srate_string.parse::<f64>().ok();
 
-    let name = get_text_from_child(&xml, "name").ok();
-    let stream_type = get_text_from_child(&xml, "type").ok();
+    let name = get_text_from_child(xml, "name").ok();
+    let stream_type = get_text_from_child(xml, "type").ok();
+    let source_id = get_text_from_child(xml, "source_id").ok();
 
-    let info = StreamHeaderChunkInfo {
+    Ok(StreamHeaderChunkInfo {
         channel_count,
         nominal_srate,
         channel_format,
         name,
         stream_type,
-    };
+        source_id,
+    })
+}
+
+// StreamHeaderChunk contains streamID, info, and xml
+// the info contains channel count, nominal_srate, format, name, and type
+pub(crate) fn stream_header<'a>(input: &'a [u8], limits: &ParseLimits) -> IResult<&'a [u8], StreamHeaderChunk> {
+    let (input, chunk_content) = context("stream_header chunk_content", chunk_content)(input)?;
+
+    let (chunk_content, _) = context("stream_header tag", stream_header_tag)(chunk_content)?;
+    let (chunk_content, stream_id) = context("stream_header stream_id", stream_id)(chunk_content)?;
+    let (_chunk_content, xml) = context("stream_header xml", |i| xml(i))(chunk_content)?; // subtract 2 for the length field and 4 for the stream_id field
+
+    let info = checked_stream_header_info(&xml).map_err(|reason| failure(chunk_content, reason))?;
+    check_limit(
+        chunk_content,
+        Limit::Channels,
+        u64::from(info.channel_count),
+        u64::from(limits.max_channels),
+    )?;
 
     Ok((input, StreamHeaderChunk { stream_id, info, xml }))
 }
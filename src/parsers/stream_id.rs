@@ -1,4 +1,6 @@
-use nom::{number::complete::le_u32, IResult};
+use nom::number::complete::le_u32;
+
+use super::IResult;
 
 pub(super) fn stream_id(input: &[u8]) -> IResult<&[u8], u32> {
     le_u32(input)
@@ -0,0 +1,24 @@
+// structure of a chunk with a tag not defined by the specification:
+// [Tag] [Content]
+// [2] [Variable]
+
+use nom::{error::context, number::complete::le_u16};
+
+use crate::chunk_structs::UnknownChunk;
+
+use super::{chunk_content, IResult};
+
+// keeps the content as it is, as nothing is known about its structure
+pub(crate) fn unknown(input: &[u8]) -> IResult<&[u8], UnknownChunk> {
+    let (input, chunk_content) = context("unknown chunk_content", chunk_content)(input)?;
+
+    let (content, tag) = context("unknown tag", le_u16)(chunk_content)?;
+
+    Ok((
+        input,
+        UnknownChunk {
+            tag,
+            content: content.to_vec(),
+        },
+    ))
+}
@@ -1,19 +1,28 @@
-use nom::{combinator, error::context, multi, number, IResult};
+use nom::{error::context, multi, number};
 
-use crate::{Format, Values};
+use crate::{
+    errors::{Limit, ParseErrorReason},
+    Format, Values,
+};
 
-use super::chunk_length::length;
+use super::{
+    chunk_length::length,
+    error::{check_limit, error},
+    IResult,
+};
 
 // string value structure
 // [NumLengthBytes] [Length] [StringContent]
 // [1, 4, or 8] [...] [Arbitrary]
 // [1] [As encoded] [Length]
 
-fn string_value(input: &[u8]) -> IResult<&[u8], String> {
-    let (input, length) = length(input)?;
+fn string_value(input: &[u8], max_length: u64) -> IResult<&[u8], String> {
+    let (rest, length) = length(input)?;
+    check_limit(input, Limit::StringLength, length as u64, max_length)?;
+    let input = rest;
     let (input, string_bytes) = nom::bytes::complete::take(length)(input)?;
     let Ok(string) = String::from_utf8(string_bytes.to_vec()) else {
-        return context("string_value invalid utf8", combinator::fail)(&[0]);
+        return Err(error(string_bytes, ParseErrorReason::InvalidUtf8));
     };
 
     Ok((input, string))
@@ -24,7 +33,12 @@ fn string_value(input: &[u8]) -> IResult<&[u8], String> {
 // [Arbitrary]
 // [8, 4, 2 or 1]
 
-pub(super) fn values(input: &[u8], format: Format, num_values: usize) -> IResult<&[u8], Values> {
+pub(super) fn values(
+    input: &[u8],
+    format: Format,
+    num_values: usize,
+    max_string_length: u64,
+) -> IResult<&[u8], Values> {
     let mut input = input;
     let values = match format {
         Format::Float32 => {
@@ -58,7 +72,7 @@ pub(super) fn values(input: &[u8], format: Format, num_values: usize) -> IResult
             Values::Int64(values)
         }
         Format::String => {
-            let (inp, string) = context("values String", string_value)(input)?;
+            let (inp, string) = context("values String", |i| string_value(i, max_string_length))(input)?;
             input = inp;
             Values::String(string)
         }
@@ -1,10 +1,17 @@
-use std::{cell::RefCell, collections::HashMap, ops::Deref, rc::Rc};
+use std::collections::HashMap;
 
-use nom::{branch::alt, bytes::complete::tag, combinator::map, error::context, multi::many0, IResult};
+use nom::{bytes::complete::tag, combinator::map, error::context, number::complete::le_u16};
 
-use crate::chunk_structs::{Chunk, StreamHeaderChunkInfo};
+use crate::{
+    chunk_structs::{Chunk, ChunkKind, StreamHeaderChunkInfo},
+    errors::ParseErrorReason,
+    ParseLimits,
+};
 
-use super::{boundary, clock_offset, file_header, samples, stream_footer, stream_header};
+use super::{
+    boundary, chunk_content, clock_offset, error::error, file_header, samples, stream_footer, stream_header, unknown,
+    IResult,
+};
 
 // structure of an XDF file:
 // [MagicCode] [Chunk] [Chunk] [Chunk] ...
@@ -16,54 +23,41 @@ use super::{boundary, clock_offset, file_header, samples, stream_footer, stream_
 // [1, 4, or 8] [...] [Tag number] [Arbitrary]
 // [1] [As coded in NumLengthBytes] [2] [Variable]
 
-fn magic_number(input: &[u8]) -> IResult<&[u8], &[u8]> {
+pub(crate) fn magic_number(input: &[u8]) -> IResult<&[u8], &[u8]> {
     context("magic_number", tag(b"XDF:"))(input)
 }
 
-// parses the magic number, the file header, and then all the rest of the chunks. Returns a vector of chunks
-pub(crate) fn xdf_file_parser(input: &[u8]) -> IResult<&[u8], Vec<Chunk>> {
-    let stream_info_map: HashMap<u32, StreamHeaderChunkInfo> = HashMap::new();
-    let cursed: Rc<RefCell<HashMap<u32, StreamHeaderChunkInfo>>> = Rc::new(RefCell::new(stream_info_map));
-
-    let file_header_parser = map(file_header, Chunk::FileHeader);
-    let mut file_header_parser = context("xdf_file file_header", file_header_parser);
-
-    let stream_header_parser = map(stream_header, |stream_header_chunk| {
-        let mut stream_info_map = cursed.deref().borrow_mut();
-        stream_info_map.insert(stream_header_chunk.stream_id, stream_header_chunk.info.clone());
-        Chunk::StreamHeader(stream_header_chunk)
-    });
-    let stream_header_parser = context("xdf_file stream_header", stream_header_parser);
-
-    let samples_parser = map(|input| samples(input, cursed.clone()), Chunk::Samples);
-    let samples_parser = context("xdf_file samples", samples_parser);
-
-    let clock_offset_parser = map(clock_offset, Chunk::ClockOffset);
-    let clock_offset_parser = context("xdf_file clock_offset", clock_offset_parser);
-
-    let boundary_parser = map(boundary, Chunk::Boundary);
-    let boundary_parser = context("xdf_file boundary", boundary_parser);
-
-    let stream_footer_parser = map(stream_footer, Chunk::StreamFooter);
-    let stream_footer_parser = context("xdf_file stream_footer", stream_footer_parser);
-
-    let repeated_parsers = many0(alt((
-        stream_header_parser,
-        samples_parser,
-        clock_offset_parser,
-        boundary_parser,
-        stream_footer_parser,
-    )));
-    let mut repeated_parsers = context("xdf_file repeated_parsers", repeated_parsers);
-
-    let (input, _) = magic_number(input)?;
-    let (input, file_header) = file_header_parser(input)?;
-    let (input, other_chunks) = repeated_parsers(input)?;
-
-    let mut chunks = vec![file_header];
-    chunks.extend(other_chunks);
-
-    Ok((input, chunks))
+// parses a single chunk of any type.
+// the stream info is needed to parse samples chunks and must contain the info of all stream headers parsed so far
+pub(crate) fn chunk<'a>(
+    input: &'a [u8],
+    stream_info: &HashMap<u32, StreamHeaderChunkInfo>,
+    limits: &ParseLimits,
+) -> IResult<&'a [u8], Chunk> {
+    // the tag decides which parser is used, so that its error is the one that is returned
+    let (_, content) = context("xdf_file chunk_content", chunk_content)(input)?;
+    let (_, tag) = context("xdf_file tag", le_u16)(content)?;
+
+    match ChunkKind::from_tag(tag) {
+        Some(ChunkKind::FileHeader) => context("xdf_file file_header", map(file_header, Chunk::FileHeader))(input),
+        Some(ChunkKind::StreamHeader) => context(
+            "xdf_file stream_header",
+            map(|input| stream_header(input, limits), Chunk::StreamHeader),
+        )(input),
+        Some(ChunkKind::Samples) => context(
+            "xdf_file samples",
+            map(|input| samples(input, stream_info, limits), Chunk::Samples),
+        )(input),
+        Some(ChunkKind::ClockOffset) => context("xdf_file clock_offset", map(clock_offset, Chunk::ClockOffset))(input),
+        Some(ChunkKind::Boundary) => context("xdf_file boundary", map(boundary, Chunk::Boundary))(input),
+        Some(ChunkKind::StreamFooter) => {
+            context("xdf_file stream_footer", map(stream_footer, Chunk::StreamFooter))(input)
+        }
+        // a zero tag is rather a sign of a corrupted chunk, for example of bytes zeroed after a crash
+        None if tag == 0 => Err(error(content, ParseErrorReason::UnknownTag(tag))),
+        // chunks of later versions of the specification or vendor extensions are skipped by their length
+        None => context("xdf_file unknown", map(unknown, Chunk::Unknown))(input),
+    }
 }
 
 #[cfg(test)]
@@ -74,9 +68,18 @@ mod tests {
         // load minimal.xdf which is included in the repo
         let input = include_bytes!("../../tests/minimal.xdf");
 
-        let (rest, chunks) = xdf_file_parser(input).unwrap();
+        let (mut rest, _) = magic_number(input).unwrap();
+        let mut stream_info = HashMap::new();
+        let mut chunks = Vec::new();
+        while !rest.is_empty() {
+            let (input, chunk) = chunk(rest, &stream_info, &ParseLimits::default()).unwrap();
+            if let Chunk::StreamHeader(c) = &chunk {
+                stream_info.insert(c.stream_id, c.info.clone());
+            }
+            chunks.push(chunk);
+            rest = input;
+        }
 
-        assert_eq!(rest, &[] as &[u8]);
         assert_eq!(chunks.len(), 15);
 
         assert!(matches!(chunks[0], Chunk::FileHeader(_)));
@@ -95,4 +98,19 @@ mod tests {
         assert!(matches!(chunks[13], Chunk::StreamFooter(_)));
         assert!(matches!(chunks[14], Chunk::StreamFooter(_)));
     }
+
+    #[test]
+    fn test_unknown_tag() {
+        let input = [1, 4, 0x2A, 0x01, 0xAB, 0xCD, 0xFF];
+        let (rest, parsed) = chunk(&input, &HashMap::new(), &ParseLimits::default()).unwrap();
+        assert_eq!(rest, [0xFF]);
+        let Chunk::Unknown(unknown) = parsed else {
+            panic!("expected an unknown chunk, got {parsed:?}");
+        };
+        assert_eq!(unknown.tag, 0x012A);
+        assert_eq!(unknown.content, [0xAB, 0xCD]);
+
+        // no chunk has tag 0
+        assert!(chunk(&[1, 2, 0, 0], &HashMap::new(), &ParseLimits::default()).is_err());
+    }
 }
@@ -1,11 +1,12 @@
-use nom::{combinator, error::context, IResult};
 use xmltree::Element;
 
+use crate::errors::ParseErrorReason;
+
+use super::{error::error, IResult};
+
 // bit of an odd one but so be it
 pub(crate) fn xml(input: &[u8]) -> IResult<&[u8], Element> {
-    let Ok(xml) = Element::parse(input) else {
-        return context("xml error parsing xml", combinator::fail)(input);
-    };
+    let xml = Element::parse(input).map_err(|e| error(input, ParseErrorReason::InvalidXml(e.to_string())))?;
 
     Ok((input, xml))
 }
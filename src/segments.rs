@@ -0,0 +1,265 @@
+use std::ops::Range;
+
+use crate::{sync::least_squares, ClockOffsetChunk, Sample, Segmentation};
+
+/// A contiguous part of a regularly sampled stream, without gaps or clock resets between its samples.
+/// See [`crate::Segmentation`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    /// The index of the first sample of the segment in [`crate::Stream::samples`].
+    pub start: usize,
+    /// The index after the last sample of the segment, so the samples are `start..end`.
+    pub end: usize,
+    /// The timestamp of the first sample of the segment.
+    pub first_timestamp: f64,
+    /// The timestamp of the last sample of the segment.
+    pub last_timestamp: f64,
+    /// The sampling rate calculated from the timestamps of the segment, or None if it has less than two samples.
+    pub effective_srate: Option<f64>,
+}
+
+impl Segment {
+    /// The indices of the samples of the segment.
+    #[must_use]
+    pub const fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    // describes the samples in the range, which must all have a timestamp
+    fn new(range: Range<usize>, samples: &[Sample]) -> Option<Self> {
+        let first_timestamp = samples.get(range.start)?.timestamp?;
+        let last_timestamp = samples.get(range.end.checked_sub(1)?)?.timestamp?;
+
+        let duration = last_timestamp - first_timestamp;
+        let effective_srate = if range.len() > 1 && duration > 0.0 && duration.is_finite() {
+            Some((range.len() - 1) as f64 / duration)
+        } else {
+            None
+        };
+
+        Some(Self {
+            start: range.start,
+            end: range.end,
+            first_timestamp,
+            last_timestamp,
+            effective_srate,
+        })
+    }
+}
+
+// splits the samples of a regularly sampled stream into segments wherever the time between two samples is too long,
+// the clock was reset or the samples were corrected with another part of the clock offsets.
+// Streams without a positive sampling rate or with samples without a timestamp are not split.
+pub(crate) fn find_segments(
+    samples: &[Sample],
+    clock_segments: &[Option<usize>],
+    nominal_srate: Option<f64>,
+    segmentation: &Segmentation,
+) -> Vec<Range<usize>> {
+    let Some(srate) = nominal_srate.filter(|srate| *srate > 0.0) else {
+        return Vec::new();
+    };
+    let Some(timestamps) = samples.iter().map(|s| s.timestamp).collect::<Option<Vec<f64>>>() else {
+        return Vec::new();
+    };
+    if timestamps.is_empty() {
+        return Vec::new();
+    }
+
+    let threshold = segmentation.min_gap_seconds.max(segmentation.max_gap_samples / srate);
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for (i, pair) in timestamps.windows(2).enumerate() {
+        let delta = pair[1] - pair[0];
+        let is_gap = delta > threshold;
+        let is_reset = segmentation.detect_clock_resets && delta < 0.0;
+        let is_new_clock_segment = clock_segments.get(i) != clock_segments.get(i + 1);
+
+        if is_gap || is_reset || is_new_clock_segment {
+            ranges.push(start..i + 1);
+            start = i + 1;
+        }
+    }
+    ranges.push(start..timestamps.len());
+
+    ranges
+}
+
+// splits the clock offsets wherever the clock was reset, or returns a single range if resets are not detected.
+pub(crate) fn clock_segments(offsets: &[ClockOffsetChunk], segmentation: &Segmentation) -> Vec<Range<usize>> {
+    if offsets.is_empty() {
+        return Vec::new();
+    }
+    if !segmentation.detect_clock_resets {
+        return std::iter::once(0..offsets.len()).collect();
+    }
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for (i, pair) in offsets.windows(2).enumerate() {
+        let time_jumps_back = pair[1].collection_time < pair[0].collection_time;
+        let offset_jumps = (pair[1].offset_value - pair[0].offset_value).abs() > segmentation.max_offset_jump;
+
+        if time_jumps_back || offset_jumps {
+            ranges.push(start..i + 1);
+            start = i + 1;
+        }
+    }
+    ranges.push(start..offsets.len());
+
+    ranges
+}
+
+// the earliest and latest collection time of each part of the clock offsets, see clock_segment_for
+pub(crate) fn clock_segment_bounds(offsets: &[ClockOffsetChunk], ranges: &[Range<usize>]) -> Vec<(f64, f64)> {
+    ranges
+        .iter()
+        .map(|range| {
+            offsets[range.clone()]
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(first, last), o| {
+                    (first.min(o.collection_time), last.max(o.collection_time))
+                })
+        })
+        .collect()
+}
+
+// the index of the part of the clock offsets whose collection times, given by their bounds, are closest to the timestamp.
+// If several parts contain it, the first one is used. There must be at least one part.
+pub(crate) fn clock_segment_for(timestamp: f64, bounds: &[(f64, f64)]) -> usize {
+    bounds
+        .iter()
+        .map(|&(first, last)| (first - timestamp).max(timestamp - last).max(0.0))
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map_or(0, |(k, _)| k)
+}
+
+// replaces the timestamps of every segment by evenly spaced ones, fitted to them by least squares
+pub(crate) fn dejitter(samples: &mut [Sample], ranges: &[Range<usize>]) {
+    for range in ranges {
+        let segment = &mut samples[range.clone()];
+        // segments only contain samples with a timestamp
+        let points: Vec<(f64, f64)> = segment
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| s.timestamp.map(|ts| (i as f64, ts)))
+            .collect();
+
+        if let Some(fit) = least_squares(points.into_iter()) {
+            for (i, sample) in segment.iter_mut().enumerate() {
+                sample.timestamp = Some(fit.value_at(i as f64));
+            }
+        }
+    }
+}
+
+// describes the segments once the timestamps are final
+pub(crate) fn segments(ranges: Vec<Range<usize>>, samples: &[Sample]) -> Vec<Segment> {
+    ranges
+        .into_iter()
+        .filter_map(|range| Segment::new(range, samples))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Values;
+
+    fn samples(timestamps: &[f64]) -> Vec<Sample> {
+        timestamps
+            .iter()
+            .map(|&ts| Sample {
+                timestamp: Some(ts),
+                values: Values::Int8(vec![0]),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_segments() {
+        let timestamps: Vec<f64> = (0..10)
+            .map(|i| f64::from(i) * 0.1)
+            .chain((0..5).map(|i| 100.0 + f64::from(i) * 0.1))
+            .collect();
+        let samples = samples(&timestamps);
+
+        let segmentation = Segmentation::default();
+        assert_eq!(
+            find_segments(&samples, &[], Some(10.0), &segmentation),
+            vec![0..10, 10..15]
+        );
+        assert!(find_segments(&samples, &[], None, &segmentation).is_empty());
+        // a gap has to be longer than 500 samples
+        assert_eq!(find_segments(&samples, &[], Some(1.0), &segmentation), vec![0..15]);
+
+        let short_gaps = Segmentation {
+            max_gap_samples: 1.5,
+            min_gap_seconds: 0.0,
+            ..Segmentation::default()
+        };
+        assert_eq!(
+            find_segments(&samples, &[], Some(1.0), &short_gaps),
+            vec![0..10, 10..15]
+        );
+
+        // the timestamps jump back and the later samples are corrected with other clock offsets
+        let mut samples = samples;
+        samples[4].timestamp = Some(0.0);
+        let clock_segments: Vec<Option<usize>> = (0..15).map(|i| Some(usize::from(i >= 12))).collect();
+        let with_resets = Segmentation {
+            detect_clock_resets: true,
+            ..Segmentation::default()
+        };
+        assert_eq!(
+            find_segments(&samples, &clock_segments, Some(10.0), &with_resets),
+            vec![0..4, 4..10, 10..12, 12..15]
+        );
+    }
+
+    #[test]
+    fn test_clock_segments() {
+        let offsets: Vec<ClockOffsetChunk> = [(0.0, 0.1), (5.0, 0.1), (10.0, 5.0), (15.0, 5.0), (1.0, 5.0)]
+            .iter()
+            .map(|&(collection_time, offset_value)| ClockOffsetChunk {
+                stream_id: 0,
+                collection_time,
+                offset_value,
+            })
+            .collect();
+
+        let with_resets = Segmentation {
+            detect_clock_resets: true,
+            ..Segmentation::default()
+        };
+        let ranges = clock_segments(&offsets, &with_resets);
+        assert_eq!(ranges, vec![0..2, 2..4, 4..5]);
+        let bounds = clock_segment_bounds(&offsets, &ranges);
+        assert_eq!(bounds, vec![(0.0, 5.0), (10.0, 15.0), (1.0, 1.0)]);
+        assert_eq!(clock_segment_for(6.0, &bounds), 0);
+        assert_eq!(clock_segment_for(9.0, &bounds), 1);
+        assert_eq!(clock_segment_for(30.0, &bounds), 1);
+
+        let without_resets = Segmentation::default();
+        assert_eq!(clock_segments(&offsets, &without_resets), vec![0..5]);
+        assert!(clock_segments(&[], &without_resets).is_empty());
+    }
+
+    #[test]
+    fn test_dejitter() {
+        let mut samples = samples(&[1.0, 1.12, 1.18, 1.31, 1.39, 5.0, 5.11, 5.19]);
+        let ranges = vec![0..5, 5..8];
+        dejitter(&mut samples, &ranges);
+
+        let segments = segments(ranges, &samples);
+        for pair in samples[0..5].windows(2) {
+            let spacing = pair[1].timestamp.unwrap() - pair[0].timestamp.unwrap();
+            assert!((spacing - 0.097).abs() < 1E-9);
+        }
+        assert!((segments[0].effective_srate.unwrap() - 1.0 / 0.097).abs() < 1E-6);
+        assert!((segments[1].effective_srate.unwrap() - 1.0 / 0.095).abs() < 1E-6);
+        assert_eq!(segments[1].range(), 5..8);
+    }
+}
@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
-use crate::{Format, Sample};
+use crate::{
+    channels, montage, ChannelInfo, ClockOffsetStats, Format, Montage, Sample, Segment, StreamFooterChunkInfo,
+};
 
 // minimal tags in version 1.0:
 // channel count
@@ -12,21 +14,125 @@ use crate::{Format, Sample};
 // type
 // desc
 
+/// A single stream of an XDF file, combining its header, footer and samples.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Stream {
+    /// The stream ID as given in the file.
     pub id: u32, // not really necessary but nie for debugging and testing
+    /// The byte offset of the stream header chunk from the start of the file. [`crate::XDFFile::streams`] are sorted by it.
+    pub header_offset: u64,
+    /// The number of channels, i.e. the number of values per sample.
     pub channel_count: u32,
+    /// The nominal sampling rate in Hz, or None for streams with an irregular sampling rate.
     pub nominal_srate: Option<f64>, //a mandatory field but we replace zero with None
+    /// The format of the values in this stream.
     pub format: Format,
 
     // optional fields:
+    /// The name of the stream, if given in the header.
     pub name: Option<Arc<str>>,
+    /// The type of the stream (for example "EEG" or "Markers"), if given in the header.
     pub r#type: Option<Arc<str>>,
 
+    /// The XML header of the stream as an [`xmltree::Element`].
     pub header: xmltree::Element, //contains desc
+    /// The XML footer of the stream, if the file contains one.
     pub footer: Option<xmltree::Element>,
+    /// The fields parsed from the footer, if the file contains one.
+    pub footer_info: Option<StreamFooterChunkInfo>,
 
+    /// The sampling rate calculated from the number of samples and the time between the first and last one.
     pub measured_srate: Option<f64>,
 
+    /// The clock offsets of this stream as pairs of collection time and offset value, both in seconds, in the order they
+    /// appear in the file. Offsets which are not finite are only included if
+    /// [`crate::ParseOptions::drop_non_finite_clock_offsets`] is disabled.
+    /// The [`Stream::raw_timestamps`] of regularly sampled streams are corrected by them. They are written back to the file.
+    pub clock_offsets: Vec<(f64, f64)>,
+
+    /// The samples of this stream, sorted by timestamp. Their timestamps are the [`Stream::corrected_timestamps`],
+    /// dejittered if [`crate::ParseOptions::dejitter`] is enabled.
     pub samples: Vec<Sample>,
+
+    /// The timestamps of the samples as recorded in the file, or None for samples recorded without one.
+    /// This and the next two columns are empty if [`crate::ParseOptions::timestamp_columns`] is disabled.
+    pub raw_timestamps: Vec<Option<f64>>,
+    /// The recorded timestamps, with the missing ones calculated from the previous timestamp and the nominal sampling rate.
+    pub interpolated_timestamps: Vec<Option<f64>>,
+    /// The interpolated timestamps corrected by the clock offsets. Irregularly sampled streams are not corrected.
+    pub corrected_timestamps: Vec<Option<f64>>,
+
+    /// The contiguous parts of a regularly sampled stream, split at gaps and clock resets, see [`crate::Segmentation`].
+    /// Empty for streams without a nominal sampling rate or with samples without a timestamp.
+    pub segments: Vec<Segment>,
+
+    /// How well the clock offsets of this stream could be measured, see [`Stream::clock_offsets`].
+    pub clock_offset_stats: ClockOffsetStats,
+}
+
+impl Stream {
+    /**
+    The metadata of the channels, parsed from `desc/channels/channel` in the header as described by the XDF meta-data
+    conventions. Channels without a label, and all channels of streams without `desc/channels`, get generated labels,
+    so there is always one entry per channel. Channels described beyond the channel count are left out.
+
+    # Example
+    ```rust
+    # use std::fs;
+    # use xdf::XDFFile;
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let bytes = fs::read("tests/minimal.xdf")?;
+    let xdf_file = XDFFile::from_bytes(&bytes)?;
+    for channel in xdf_file.streams[0].channels() {
+        println!("{} ({})", channel.label, channel.unit.as_deref().unwrap_or("no unit"));
+    }
+    # Ok(())
+    # }
+    ```
+    */
+    #[must_use]
+    pub fn channels(&self) -> Vec<ChannelInfo> {
+        channels::channels(&self.header, self.channel_count)
+    }
+
+    /**
+    The electrode positions of an EEG stream, parsed from `desc/channels/channel/location` and `desc/cap` in the header
+    as described by the XDF meta-data conventions. None if no channel has a complete location.
+
+    # Example
+    ```rust
+    # use std::fs;
+    # use xdf::XDFFile;
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let bytes = fs::read("tests/minimal.xdf")?;
+    let xdf_file = XDFFile::from_bytes(&bytes)?;
+    if let Some(montage) = xdf_file.streams[0].montage() {
+        fs::write("montage.elp", montage.to_elp())?;
+    }
+    # Ok(())
+    # }
+    ```
+    */
+    #[must_use]
+    pub fn montage(&self) -> Option<Montage> {
+        montage::montage(&self.header, &self.channels())
+    }
+
+    /// The sampling rate calculated from the timestamps of all segments with at least two samples together,
+    /// like pyxdf's `effective_srate`. None if there is no such segment.
+    #[must_use]
+    pub fn effective_srate(&self) -> Option<f64> {
+        let (intervals, duration) =
+            self.segments
+                .iter()
+                .filter(|s| s.end - s.start > 1)
+                .fold((0, 0.0), |(intervals, duration), s| {
+                    (
+                        intervals + s.end - s.start - 1,
+                        duration + s.last_timestamp - s.first_timestamp,
+                    )
+                });
+
+        (duration > 0.0 && duration.is_finite()).then(|| intervals as f64 / duration)
+    }
 }
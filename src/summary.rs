@@ -0,0 +1,102 @@
+use std::{io::Read, sync::Arc};
+
+use crate::{
+    check_footers, next_chunk, read_file_header, truncation_diagnostic, Chunk, ChunkReader, Diagnostic, DiagnosticKind,
+    FileHeader, Format, ParseOptions, StreamError, StreamFooterChunkInfo, XDFError,
+};
+
+/// An overview of an XDF file as returned by [`crate::XDFFile::probe`], containing everything but the samples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XDFSummary {
+    /// The file header with the XDF version and the time the recording was started.
+    pub header: FileHeader,
+    /// The streams in the order of their headers in the file.
+    pub streams: Vec<StreamSummary>,
+    /// The problems found while reading the file, like in [`crate::XDFFile::diagnostics`].
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// An overview of a single stream, as contained in an [`XDFSummary`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamSummary {
+    /// The stream ID as given in the file.
+    pub id: u32,
+    /// The number of channels, i.e. the number of values per sample.
+    pub channel_count: u32,
+    /// The nominal sampling rate in Hz, or None for streams with an irregular sampling rate.
+    pub nominal_srate: Option<f64>,
+    /// The format of the values in this stream.
+    pub format: Format,
+
+    /// The name of the stream, if given in the header.
+    pub name: Option<Arc<str>>,
+    /// The type of the stream (for example "EEG" or "Markers"), if given in the header.
+    pub r#type: Option<Arc<str>>,
+
+    /// The XML header of the stream as an [`xmltree::Element`].
+    pub header: xmltree::Element,
+    /// The XML footer of the stream, if the file contains one.
+    /// Recorders like `LabRecorder` write the first and last timestamp as well as the sample count to it.
+    pub footer: Option<xmltree::Element>,
+    /// The fields parsed from the footer, if the file contains one, like [`crate::Stream::footer_info`].
+    pub footer_info: Option<StreamFooterChunkInfo>,
+}
+
+// reads all chunks except for the samples, which are skipped by their length.
+// Only the options which apply to the other chunks are used.
+pub(crate) fn summarize<R: Read>(chunk_reader: ChunkReader<R>, options: &ParseOptions) -> Result<XDFSummary, XDFError> {
+    let mut chunk_reader = chunk_reader
+        .skip_samples(true)
+        .recover(options.recover)
+        .limits(options.limits);
+
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let file_header_chunk = read_file_header(&mut chunk_reader, options.allow_unknown_versions, &mut diagnostics)?;
+    let mut streams: Vec<StreamSummary> = Vec::new();
+    let mut header_offsets = Vec::new();
+    let mut footer_offsets = Vec::new();
+
+    while let Some(located_chunk) = next_chunk(&mut chunk_reader, &mut diagnostics)? {
+        let offset = located_chunk.offset;
+        match located_chunk.chunk {
+            Chunk::StreamHeader(c) => {
+                header_offsets.push((c.stream_id, offset));
+                streams.push(StreamSummary {
+                    id: c.stream_id,
+                    channel_count: c.info.channel_count,
+                    nominal_srate: c.info.nominal_srate,
+                    format: c.info.channel_format,
+                    name: c.info.name.as_deref().map(Arc::from),
+                    r#type: c.info.stream_type.as_deref().map(Arc::from),
+                    header: c.xml,
+                    footer: None,
+                    footer_info: None,
+                });
+            }
+            Chunk::StreamFooter(c) => {
+                footer_offsets.push((c.stream_id, offset));
+                if let Some(stream) = streams.iter_mut().find(|s| s.id == c.stream_id) {
+                    stream.footer = Some(c.xml);
+                    stream.footer_info = Some(c.info);
+                }
+            }
+            Chunk::FileHeader(_) if !options.recover => return Err(StreamError::MultipleFileHeader.into()),
+            Chunk::FileHeader(_) => {
+                diagnostics.push(Diagnostic::new(DiagnosticKind::MultipleFileHeader, None, Some(offset)));
+            }
+            Chunk::Unknown(_) => diagnostics.push(Diagnostic::new(DiagnosticKind::UnknownChunk, None, Some(offset))),
+            _ => (),
+        }
+    }
+
+    if let Some(truncation) = chunk_reader.truncation() {
+        diagnostics.push(truncation_diagnostic(truncation));
+    }
+    check_footers(&header_offsets, &footer_offsets, &mut diagnostics);
+
+    Ok(XDFSummary {
+        header: file_header_chunk.into(),
+        streams,
+        diagnostics,
+    })
+}
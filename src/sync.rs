@@ -0,0 +1,279 @@
+// fits a linear model of the clock offsets over their collection time, like pyxdf's `synchronize_clocks`
+
+use std::ops::Range;
+
+use crate::{ClockOffsetChunk, ClockSync};
+
+// the number of ADMM iterations and the penalty parameter pyxdf uses for its robust fit
+const ROBUST_FIT_ITERATIONS: usize = 1000;
+const RHO: f64 = 1.0;
+
+/// How well the clock offsets of a stream could be measured, see [`crate::Stream::clock_offset_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ClockOffsetStats {
+    /**
+    How fast the clocks drift apart in parts per million, i.e. microseconds per second.
+    This is the slope of a line fitted to the offsets over their collection time, separately for each part between
+    clock resets. None if no two offsets were collected at different times.
+    */
+    pub drift_ppm: Option<f64>,
+    /// The standard deviation of the offsets around the fitted line, in seconds. None if there are less than three offsets.
+    pub jitter: Option<f64>,
+    /// The number of offsets which were dropped because they are not finite, see
+    /// [`crate::ParseOptions::drop_non_finite_clock_offsets`]. Offsets which are not finite are left out of the other
+    /// statistics either way.
+    pub rejected: usize,
+}
+
+// the statistics of the offsets, which are split into the given parts at clock resets
+pub(crate) fn offset_stats(offsets: &[ClockOffsetChunk], ranges: &[Range<usize>], rejected: usize) -> ClockOffsetStats {
+    let finite_points = |range: &Range<usize>| {
+        offsets[range.clone()]
+            .iter()
+            .filter(|o| o.collection_time.is_finite() && o.offset_value.is_finite())
+            .map(|o| (o.collection_time, o.offset_value))
+    };
+
+    // the means of every part, as the parts don't share an intercept
+    let means: Vec<(f64, f64)> = ranges
+        .iter()
+        .map(|range| {
+            let (count, sum_x, sum_y) =
+                finite_points(range).fold((0.0, 0.0, 0.0), |(n, sx, sy), (x, y)| (n + 1.0, sx + x, sy + y));
+            if count > 0.0 {
+                (sum_x / count, sum_y / count)
+            } else {
+                (0.0, 0.0)
+            }
+        })
+        .collect();
+    let centered = || {
+        ranges.iter().zip(&means).flat_map(move |(range, &(mean_x, mean_y))| {
+            finite_points(range).map(move |(x, y)| (x - mean_x, y - mean_y))
+        })
+    };
+
+    let (count, covariance, variance) = centered().fold((0_usize, 0.0, 0.0), |(n, c, v), (dx, dy)| {
+        (n + 1, dx.mul_add(dy, c), dx.mul_add(dx, v))
+    });
+    let slope = (variance > 0.0).then(|| covariance / variance);
+
+    let jitter = (count >= 3).then(|| {
+        let squared_residuals: f64 = centered()
+            .map(|(dx, dy)| {
+                let residual = slope.unwrap_or(0.0).mul_add(-dx, dy);
+                residual * residual
+            })
+            .sum();
+        (squared_residuals / count as f64).sqrt()
+    });
+
+    ClockOffsetStats {
+        drift_ppm: slope.map(|slope| slope * 1E6),
+        jitter,
+        rejected,
+    }
+}
+
+/// A linear model `y = intercept + slope * x`, for example of the clock offset over time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LinearFit {
+    pub(crate) intercept: f64,
+    pub(crate) slope: f64,
+}
+
+impl LinearFit {
+    // the modelled y at the given x
+    pub(crate) fn value_at(self, x: f64) -> f64 {
+        self.slope.mul_add(x, self.intercept)
+    }
+}
+
+// fits the clock offsets as requested by the option, or None if they are interpolated instead.
+pub(crate) fn fit(clock_sync: ClockSync, offsets: &[ClockOffsetChunk]) -> Option<LinearFit> {
+    match clock_sync {
+        ClockSync::Interpolate => None,
+        ClockSync::Robust { winsor_threshold } if winsor_threshold > 0.0 && winsor_threshold.is_finite() => {
+            robust(offsets, winsor_threshold)
+        }
+        // pyxdf falls back to an ordinary fit for thresholds which are not positive as well
+        ClockSync::LeastSquares | ClockSync::Robust { .. } => least_squares(offset_points(offsets)),
+    }
+}
+
+// the clock offsets as (time, value) points
+fn offset_points(offsets: &[ClockOffsetChunk]) -> impl ExactSizeIterator<Item = (f64, f64)> + Clone + '_ {
+    offsets.iter().map(|o| (o.collection_time, o.offset_value))
+}
+
+/// ordinary least squares fit of the points, or None if there are none.
+/// A single point (or several with the same x) gives a constant model.
+pub(crate) fn least_squares(points: impl ExactSizeIterator<Item = (f64, f64)> + Clone) -> Option<LinearFit> {
+    if points.len() == 0 {
+        return None;
+    }
+
+    // centering the x values keeps the sums small, times are usually large
+    let n = points.len() as f64;
+    let (sum_x, sum_y) = points.clone().fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+    let (mean_x, mean_y) = (sum_x / n, sum_y / n);
+
+    let (covariance, variance) = points.fold((0.0, 0.0), |(c, v), (x, y)| {
+        let dx = x - mean_x;
+        (dx.mul_add(y - mean_y, c), dx.mul_add(dx, v))
+    });
+
+    let slope = if variance > 0.0 { covariance / variance } else { 0.0 };
+
+    Some(LinearFit {
+        intercept: slope.mul_add(-mean_x, mean_y),
+        slope,
+    })
+}
+
+/// robust fit of the offsets minimizing the Huber loss via ADMM, ported from pyxdf's `_robust_fit`.
+/// Offsets and times are scaled by the threshold, so that residuals larger than it are treated as outliers.
+fn robust(offsets: &[ClockOffsetChunk], winsor_threshold: f64) -> Option<LinearFit> {
+    if offsets.is_empty() {
+        return None;
+    }
+
+    // the design matrix has a column of ones and a column of the shifted, scaled times
+    let min_time = offsets
+        .iter()
+        .map(|o| o.collection_time / winsor_threshold)
+        .fold(f64::INFINITY, f64::min);
+    let times: Vec<f64> = offsets
+        .iter()
+        .map(|o| o.collection_time / winsor_threshold - min_time)
+        .collect();
+    let values: Vec<f64> = offsets.iter().map(|o| o.offset_value / winsor_threshold).collect();
+
+    // A^T A is a 2x2 matrix, so it is solved directly instead of using a Cholesky decomposition
+    let count = times.len() as f64;
+    let sum_t: f64 = times.iter().sum();
+    let sum_tt: f64 = times.iter().map(|t| t * t).sum();
+    let det = count.mul_add(sum_tt, -(sum_t * sum_t));
+    if det.abs() <= f64::EPSILON * count * sum_tt {
+        // all offsets were collected at the same time, no slope can be fitted
+        return least_squares(offset_points(offsets));
+    }
+    let solve = |b0: f64, b1: f64| ((sum_tt * b0 - sum_t * b1) / det, (count * b1 - sum_t * b0) / det);
+
+    let aty0: f64 = values.iter().sum();
+    let aty1: f64 = times.iter().zip(&values).map(|(t, y)| t * y).sum();
+
+    // ADMM splits the residuals into a smooth part and the outliers, with the scaled dual variable in between
+    let mut split = vec![0.0; times.len()];
+    let mut dual = vec![0.0; times.len()];
+    let mut coefficients = (0.0, 0.0);
+    for _ in 0..ROBUST_FIT_ITERATIONS {
+        // x = (A^T A)^-1 (A^T y + A^T (z - u))
+        let (b0, b1) = times
+            .iter()
+            .zip(split.iter().zip(&dual))
+            .fold((aty0, aty1), |(b0, b1), (t, (z, u))| {
+                (b0 + (z - u), t.mul_add(z - u, b1))
+            });
+        coefficients = solve(b0, b1);
+
+        for ((t, y), (z, u)) in times.iter().zip(&values).zip(split.iter_mut().zip(dual.iter_mut())) {
+            let d = coefficients.1.mul_add(*t, coefficients.0) - y + *u;
+            let shrink = if d == 0.0 {
+                0.0
+            } else {
+                (1.0 - (1.0 + 1.0 / RHO) / d.abs()).max(0.0)
+            };
+            *z = (RHO / (1.0 + RHO)).mul_add(d, 1.0 / (1.0 + RHO) * shrink * d);
+            *u = d - *z;
+        }
+    }
+
+    // undo the shift of the times and the scaling of the offsets
+    let (intercept, slope) = coefficients;
+    Some(LinearFit {
+        intercept: (intercept - slope * min_time) * winsor_threshold,
+        slope,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offsets(points: &[(f64, f64)]) -> Vec<ClockOffsetChunk> {
+        points
+            .iter()
+            .map(|&(collection_time, offset_value)| ClockOffsetChunk {
+                stream_id: 0,
+                collection_time,
+                offset_value,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_least_squares() {
+        let line: Vec<(f64, f64)> = (0..10)
+            .map(|i| {
+                let t = 1000.0 + f64::from(i) * 5.0;
+                (t, 0.01 + 2E-5 * t)
+            })
+            .collect();
+        let fit = least_squares(line.iter().copied()).unwrap();
+        assert!((fit.slope - 2E-5).abs() < 1E-12);
+        assert!((fit.intercept - 0.01).abs() < 1E-9);
+
+        let constant = least_squares([(3.0, 0.5)].into_iter()).unwrap();
+        assert_eq!(
+            constant,
+            LinearFit {
+                intercept: 0.5,
+                slope: 0.0
+            }
+        );
+
+        assert!(least_squares([].into_iter()).is_none());
+    }
+
+    #[test]
+    fn test_offset_stats() {
+        // 20 ppm drift with alternating jitter of 1 ms, and a clock reset in the middle
+        let mut points: Vec<(f64, f64)> = (0..20)
+            .map(|i| {
+                let t = f64::from(i) * 5.0;
+                let jitter = if i % 4 == 0 || i % 4 == 3 { 0.001 } else { -0.001 };
+                (t, 0.01 + 2E-5 * t + jitter)
+            })
+            .collect();
+        points.extend(points.clone().iter().map(|&(t, v)| (t, v + 3.0)));
+        points.push((f64::NAN, 0.0));
+
+        let stats = offset_stats(&offsets(&points), &[0..20, 20..41], 1);
+        assert!((stats.drift_ppm.unwrap() - 20.0).abs() < 1E-6);
+        assert!((stats.jitter.unwrap() - 0.001).abs() < 1E-9);
+        assert_eq!(stats.rejected, 1);
+
+        let single = offset_stats(&offsets(&[(1.0, 0.5)]), &[0..1, 1..1], 0);
+        assert_eq!(single, ClockOffsetStats::default());
+    }
+
+    #[test]
+    fn test_robust_fit_ignores_outliers() {
+        let mut line: Vec<(f64, f64)> = (0..20)
+            .map(|i| {
+                let t = 1000.0 + f64::from(i) * 5.0;
+                (t, 0.01 + 2E-5 * t)
+            })
+            .collect();
+        line[7].1 += 0.5;
+
+        let ordinary = fit(ClockSync::LeastSquares, &offsets(&line)).unwrap();
+        let robust = fit(ClockSync::Robust { winsor_threshold: 1E-4 }, &offsets(&line)).unwrap();
+
+        let expected = 0.01 + 2E-5 * 1050.0;
+        assert!((ordinary.value_at(1050.0) - expected).abs() > 1E-2);
+        assert!((robust.value_at(1050.0) - expected).abs() < 1E-5);
+        assert!((robust.slope - 2E-5).abs() < 1E-7);
+    }
+}
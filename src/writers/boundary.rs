@@ -0,0 +1,14 @@
+// boundary structure
+// [UUID]
+// [0x43 0xA5 0x46 0xDC 0xCB 0xF5 0x41 0x0F 0xB3 0x0E 0xD5 0x46 0x73 0x83 0xCB 0xE4]
+// [16]
+
+use std::io::Write;
+
+use crate::chunk_structs::{ChunkKind, BOUNDARY_UUID};
+
+use super::chunk_content;
+
+pub(crate) fn boundary<W: Write>(out: &mut W) -> std::io::Result<()> {
+    chunk_content(out, ChunkKind::Boundary, &BOUNDARY_UUID)
+}
@@ -0,0 +1,17 @@
+// chunk structure
+// [NumLengthBytes] [Length] [Tag] [Content]
+// [1, 4, or 8] [...] [Tag number] [Arbitrary]
+// [1] [As coded in NumLengthBytes] [2] [Variable]
+
+use std::io::Write;
+
+use crate::chunk_structs::ChunkKind;
+
+use super::chunk_length::length;
+
+// the length includes the two tag bytes
+pub(crate) fn chunk_content<W: Write>(out: &mut W, tag: ChunkKind, content: &[u8]) -> std::io::Result<()> {
+    length(out, content.len() + 2)?;
+    out.write_all(&tag.to_le_bytes())?;
+    out.write_all(content)
+}
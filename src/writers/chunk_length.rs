@@ -0,0 +1,31 @@
+use std::io::Write;
+
+// length writer, the counterpart of parsers::chunk_length::length.
+// uses the smallest of 1, 4 or 8 bytes that can hold the length.
+pub(crate) fn length<W: Write>(out: &mut W, length: usize) -> std::io::Result<()> {
+    if let Ok(length) = u8::try_from(length) {
+        out.write_all(&[1, length])
+    } else if let Ok(length) = u32::try_from(length) {
+        out.write_all(&[4])?;
+        out.write_all(&length.to_le_bytes())
+    } else {
+        out.write_all(&[8])?;
+        out.write_all(&(length as u64).to_le_bytes())
+    }
+}
+
+#[test]
+fn test_length() {
+    let test_sets: [(usize, &[u8]); 4] = [
+        (0xCA, &[1, 0xCA]),
+        (0x100, &[4, 0x00, 0x01, 0x00, 0x00]),
+        (0xCAFE_CACE, &[4, 0xCE, 0xCA, 0xFE, 0xCA]),
+        (0, &[1, 0]),
+    ];
+
+    for (length_value, expected) in test_sets {
+        let mut out = Vec::new();
+        length(&mut out, length_value).unwrap();
+        assert_eq!(out, expected, "length: 0x{length_value:X}");
+    }
+}
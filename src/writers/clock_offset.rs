@@ -0,0 +1,19 @@
+// clock offset structure
+// [StreamID] [CollectionTime] [OffsetValue]
+// [Ordinal number] [Double in seconds] [Double in seconds]
+// [4] [8] [8]
+
+use std::io::Write;
+
+use crate::chunk_structs::{ChunkKind, ClockOffsetChunk};
+
+use super::chunk_content;
+
+pub(crate) fn clock_offset<W: Write>(out: &mut W, clock_offset: &ClockOffsetChunk) -> std::io::Result<()> {
+    let mut content = Vec::with_capacity(20);
+    content.extend_from_slice(&clock_offset.stream_id.to_le_bytes()); // 4 bytes
+    content.extend_from_slice(&clock_offset.collection_time.to_le_bytes()); // 8 bytes
+    content.extend_from_slice(&clock_offset.offset_value.to_le_bytes()); // 8 bytes
+
+    chunk_content(out, ChunkKind::ClockOffset, &content)
+}
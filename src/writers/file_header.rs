@@ -0,0 +1,15 @@
+use std::io::Write;
+
+use xmltree::Element;
+
+use crate::{chunk_structs::ChunkKind, errors::XDFError};
+
+use super::{chunk_content, xml};
+
+pub(crate) fn file_header<W: Write>(out: &mut W, header: &Element) -> Result<(), XDFError> {
+    let mut content = Vec::new();
+    xml(&mut content, header)?;
+
+    chunk_content(out, ChunkKind::FileHeader, &content)?;
+    Ok(())
+}
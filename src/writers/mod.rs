@@ -0,0 +1,26 @@
+mod chunk_length;
+
+mod boundary;
+mod chunk_content;
+mod clock_offset;
+mod file_header;
+mod samples;
+mod stream_footer;
+mod stream_header;
+mod unknown;
+mod values;
+mod xml;
+
+pub(crate) mod xdf_file;
+
+pub(crate) use boundary::boundary;
+pub(crate) use clock_offset::clock_offset;
+pub(crate) use file_header::file_header;
+pub(crate) use samples::samples;
+pub(crate) use stream_footer::{stream_footer, FooterStats};
+pub(crate) use stream_header::stream_header;
+pub(crate) use unknown::unknown;
+
+use chunk_content::chunk_content;
+use values::values;
+use xml::xml;
@@ -0,0 +1,71 @@
+use std::io::Write;
+
+use crate::{chunk_structs::ChunkKind, errors::WriteError, Format, Values, XDFError};
+
+use super::{chunk_content, chunk_length::length, values};
+
+// samples structure
+// [StreamID] [NumSamples] [Sample 1] [Sample 2] ... [Sample N]
+// [Ordinal number] [NumLengthBytes + Length] [Variable] ...
+// [4] [1 + 1, 4, or 8] [Variable] ...
+
+// structure of a sample:
+// [TimeStampBytes] [OptionalTimeStamp] [Value 1] [Value 2] ... [Value N]
+// [0 or 8] [Double, in seconds] [Value as defined by format] ...
+// [1][8 if TimeStampBytes==8, 0 if TimeStampBytes==0] [[Variable]] ...
+
+fn sample(out: &mut Vec<u8>, timestamp: Option<f64>, sample_values: &Values) -> std::io::Result<()> {
+    match timestamp {
+        Some(timestamp) => {
+            out.push(8);
+            out.extend_from_slice(&timestamp.to_le_bytes());
+        }
+        None => out.push(0),
+    }
+
+    values(out, sample_values)
+}
+
+// checks that the samples match the stream header, as they could not be read back otherwise
+fn check_sample(stream_id: u32, channel_count: u32, format: Format, sample_values: &Values) -> Result<(), WriteError> {
+    let sample_format = sample_values.format();
+    if sample_format != format {
+        return Err(WriteError::FormatMismatch {
+            stream_id,
+            expected: format,
+            got: sample_format,
+        });
+    }
+
+    // strings are always read as a single value, regardless of the channel count
+    let num_values = sample_values.num_values();
+    if format != Format::String && num_values != channel_count as usize {
+        return Err(WriteError::ChannelCountMismatch {
+            stream_id,
+            expected: channel_count,
+            got: num_values,
+        });
+    }
+
+    Ok(())
+}
+
+// takes the timestamps and values of the samples separately, so that they can be written with other timestamps than their own
+pub(crate) fn samples<'a, W: Write>(
+    out: &mut W,
+    stream_id: u32,
+    channel_count: u32,
+    format: Format,
+    samples: impl ExactSizeIterator<Item = (Option<f64>, &'a Values)>,
+) -> Result<(), XDFError> {
+    let mut content = Vec::from(stream_id.to_le_bytes()); // 4 bytes
+    length(&mut content, samples.len())?;
+
+    for (timestamp, sample_values) in samples {
+        check_sample(stream_id, channel_count, format, sample_values)?;
+        sample(&mut content, timestamp, sample_values)?;
+    }
+
+    chunk_content(out, ChunkKind::Samples, &content)?;
+    Ok(())
+}
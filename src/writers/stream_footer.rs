@@ -0,0 +1,107 @@
+// stream footer structure
+// [StreamID] [XML UTF8 string]
+// [Ordinal number] [[Valid XML]]
+// [4] [As determined by chunk length]
+
+use std::io::Write;
+
+use xmltree::{Element, XMLNode};
+
+use crate::{chunk_structs::ChunkKind, errors::XDFError, Sample};
+
+use super::{chunk_content, xml};
+
+pub(crate) fn stream_footer<W: Write>(out: &mut W, stream_id: u32, footer: &Element) -> Result<(), XDFError> {
+    let mut content = Vec::from(stream_id.to_le_bytes()); // 4 bytes
+    xml(&mut content, footer)?;
+
+    chunk_content(out, ChunkKind::StreamFooter, &content)?;
+    Ok(())
+}
+
+fn text_element(name: &str, text: String) -> Element {
+    let mut element = Element::new(name);
+    element.children.push(XMLNode::Text(text));
+    element
+}
+
+// builds a footer the way LabRecorder does. The timestamps are omitted if they are unknown.
+fn footer_element(
+    first_timestamp: Option<f64>,
+    last_timestamp: Option<f64>,
+    sample_count: u64,
+    clock_offsets: &[(f64, f64)],
+) -> Element {
+    let mut footer = Element::new("info");
+
+    if let Some(first_timestamp) = first_timestamp {
+        let element = text_element("first_timestamp", first_timestamp.to_string());
+        footer.children.push(XMLNode::Element(element));
+    }
+    if let Some(last_timestamp) = last_timestamp {
+        let element = text_element("last_timestamp", last_timestamp.to_string());
+        footer.children.push(XMLNode::Element(element));
+    }
+    let element = text_element("sample_count", sample_count.to_string());
+    footer.children.push(XMLNode::Element(element));
+
+    let mut offsets_element = Element::new("clock_offsets");
+    for &(time, value) in clock_offsets {
+        let mut offset = Element::new("offset");
+        offset
+            .children
+            .push(XMLNode::Element(text_element("time", time.to_string())));
+        offset
+            .children
+            .push(XMLNode::Element(text_element("value", value.to_string())));
+        offsets_element.children.push(XMLNode::Element(offset));
+    }
+    footer.children.push(XMLNode::Element(offsets_element));
+
+    footer
+}
+
+// collects what goes into a footer while samples and clock offsets are added in the order they appear in the file
+#[derive(Debug, Default)]
+pub(crate) struct FooterStats {
+    first_timestamp: Option<f64>,
+    // the index and timestamp of the most recent sample with a timestamp
+    most_recent_timestamp: Option<(u64, f64)>,
+    sample_count: u64,
+    clock_offsets: Vec<(f64, f64)>,
+}
+
+impl FooterStats {
+    pub(crate) fn add_samples(&mut self, samples: &[Sample]) {
+        for sample in samples {
+            if let Some(timestamp) = sample.timestamp {
+                self.first_timestamp.get_or_insert(timestamp);
+                self.most_recent_timestamp = Some((self.sample_count, timestamp));
+            }
+            self.sample_count += 1;
+        }
+    }
+
+    pub(crate) fn add_clock_offset(&mut self, collection_time: f64, offset_value: f64) {
+        self.clock_offsets.push((collection_time, offset_value));
+    }
+
+    // the timestamp of the last sample, calculated using the nominal srate if that sample has none itself.
+    fn last_timestamp(&self, nominal_srate: Option<f64>) -> Option<f64> {
+        let (index, timestamp) = self.most_recent_timestamp?;
+        let samples_since_ts = self.sample_count - index - 1;
+        match nominal_srate {
+            Some(srate) if srate > 0.0 && samples_since_ts > 0 => Some(timestamp + samples_since_ts as f64 / srate),
+            _ => Some(timestamp),
+        }
+    }
+
+    pub(crate) fn footer(&self, nominal_srate: Option<f64>) -> Element {
+        footer_element(
+            self.first_timestamp,
+            self.last_timestamp(nominal_srate),
+            self.sample_count,
+            &self.clock_offsets,
+        )
+    }
+}
@@ -0,0 +1,20 @@
+// stream header structure
+// [StreamID] [XML UTF8 string]
+// [Ordinal number] [[Valid XML]]
+// [4] [As determined by chunk length]
+
+use std::io::Write;
+
+use xmltree::Element;
+
+use crate::{chunk_structs::ChunkKind, errors::XDFError};
+
+use super::{chunk_content, xml};
+
+pub(crate) fn stream_header<W: Write>(out: &mut W, stream_id: u32, header: &Element) -> Result<(), XDFError> {
+    let mut content = Vec::from(stream_id.to_le_bytes()); // 4 bytes
+    xml(&mut content, header)?;
+
+    chunk_content(out, ChunkKind::StreamHeader, &content)?;
+    Ok(())
+}
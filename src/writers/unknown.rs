@@ -0,0 +1,16 @@
+// structure of a chunk with a tag not defined by the specification:
+// [Tag] [Content]
+// [2] [Variable]
+
+use std::io::Write;
+
+use crate::chunk_structs::UnknownChunk;
+
+use super::chunk_length::length;
+
+// writes the chunk back as it was read, the length includes the two tag bytes
+pub(crate) fn unknown<W: Write>(out: &mut W, chunk: &UnknownChunk) -> std::io::Result<()> {
+    length(out, chunk.content.len() + 2)?;
+    out.write_all(&chunk.tag.to_le_bytes())?;
+    out.write_all(&chunk.content)
+}
@@ -0,0 +1,30 @@
+use crate::Values;
+
+use super::chunk_length::length;
+
+// string value structure
+// [NumLengthBytes] [Length] [StringContent]
+// [1, 4, or 8] [...] [Arbitrary]
+// [1] [As encoded] [Length]
+
+// structure of a value:
+// [double, float, int64, int32, int16 or int8]
+// [Arbitrary]
+// [8, 4, 2 or 1]
+
+pub(super) fn values(out: &mut Vec<u8>, values: &Values) -> std::io::Result<()> {
+    match values {
+        Values::Float32(values) => values.iter().for_each(|v| out.extend_from_slice(&v.to_le_bytes())),
+        Values::Float64(values) => values.iter().for_each(|v| out.extend_from_slice(&v.to_le_bytes())),
+        Values::Int8(values) => values.iter().for_each(|v| out.extend_from_slice(&v.to_le_bytes())),
+        Values::Int16(values) => values.iter().for_each(|v| out.extend_from_slice(&v.to_le_bytes())),
+        Values::Int32(values) => values.iter().for_each(|v| out.extend_from_slice(&v.to_le_bytes())),
+        Values::Int64(values) => values.iter().for_each(|v| out.extend_from_slice(&v.to_le_bytes())),
+        Values::String(string) => {
+            length(out, string.len())?;
+            out.extend_from_slice(string.as_bytes());
+        }
+    }
+
+    Ok(())
+}
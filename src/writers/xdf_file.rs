@@ -0,0 +1,89 @@
+use std::io::Write;
+
+use crate::{chunk_structs::ClockOffsetChunk, errors::WriteError, XDFError, XDFFile};
+
+use super::{boundary, clock_offset, file_header, samples, stream_footer, stream_header, unknown};
+
+// structure of an XDF file:
+// [MagicCode] [Chunk] [Chunk] [Chunk] ...
+// [XDF:] [...] [...] [...] ...
+// [4] [Variable] [Variable] [Variable] ...
+
+// writes the chunks with unknown tags right after the file header, then all stream headers, then one samples chunk and the clock offsets per stream, and finally the footers.
+// The samples are written with their raw timestamps, so that they are corrected the same way when reading the file back.
+// Without raw timestamps, the samples are written with their own timestamps and without clock offsets instead.
+pub(crate) fn xdf_file<W: Write>(out: &mut W, xdf_file: &XDFFile) -> Result<(), XDFError> {
+    out.write_all(b"XDF:")?;
+    file_header(out, &xdf_file.header.to_xml())?;
+
+    for chunk in &xdf_file.unknown_chunks {
+        unknown(out, chunk)?;
+    }
+
+    for stream in &xdf_file.streams {
+        stream_header(out, stream.id, &stream.header)?;
+    }
+
+    boundary(out)?;
+
+    for stream in &xdf_file.streams {
+        if stream.raw_timestamps.is_empty() && !stream.samples.is_empty() {
+            let timestamps_and_values = stream.samples.iter().map(|s| (s.timestamp, &s.values));
+            samples(
+                out,
+                stream.id,
+                stream.channel_count,
+                stream.format,
+                timestamps_and_values,
+            )?;
+            continue;
+        }
+
+        // zipping would silently drop the samples or timestamps without a partner
+        if stream.raw_timestamps.len() != stream.samples.len() {
+            return Err(WriteError::TimestampCountMismatch {
+                stream_id: stream.id,
+                samples: stream.samples.len(),
+                raw_timestamps: stream.raw_timestamps.len(),
+            }
+            .into());
+        }
+
+        let offsets: Vec<ClockOffsetChunk> = stream
+            .clock_offsets
+            .iter()
+            .map(|&(collection_time, offset_value)| ClockOffsetChunk {
+                stream_id: stream.id,
+                collection_time,
+                offset_value,
+            })
+            .collect();
+
+        let timestamps_and_values = stream
+            .raw_timestamps
+            .iter()
+            .copied()
+            .zip(stream.samples.iter().map(|s| &s.values));
+        samples(
+            out,
+            stream.id,
+            stream.channel_count,
+            stream.format,
+            timestamps_and_values,
+        )?;
+
+        for offset in &offsets {
+            clock_offset(out, offset)?;
+        }
+    }
+
+    boundary(out)?;
+
+    for stream in &xdf_file.streams {
+        if let Some(footer) = &stream.footer {
+            stream_footer(out, stream.id, footer)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,10 @@
+use xmltree::{Element, EmitterConfig};
+
+use crate::errors::XMLError;
+
+// writes the element as a whole XML document, including the declaration
+pub(crate) fn xml(out: &mut Vec<u8>, xml: &Element) -> Result<(), XMLError> {
+    let config = EmitterConfig::new().write_document_declaration(true);
+    xml.write_with_config(out, config)?;
+    Ok(())
+}
@@ -0,0 +1,315 @@
+use std::io::Write;
+
+use xmltree::Element;
+
+use crate::{
+    chunk_structs::ClockOffsetChunk,
+    errors::WriteError,
+    parsers::stream_header_info,
+    writers::{self, FooterStats},
+    FileHeader, Format, Sample, XDFError,
+};
+
+/// The default number of bytes after which a boundary chunk is written.
+pub const DEFAULT_BOUNDARY_INTERVAL: u64 = 1 << 20;
+
+/// A handle to a stream added to an [`XDFWriter`], used to push samples and clock offsets to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StreamHandle {
+    stream_id: u32,
+}
+
+impl StreamHandle {
+    /// The ID the stream is written with.
+    #[must_use]
+    pub const fn stream_id(self) -> u32 {
+        self.stream_id
+    }
+}
+
+// everything needed to write a footer once the writer is finished
+#[derive(Debug)]
+struct StreamState {
+    stream_id: u32,
+    channel_count: u32,
+    format: Format,
+    nominal_srate: Option<f64>,
+    footer_stats: FooterStats,
+}
+
+// keeps track of how many bytes have been written
+struct CountingWriter<W: Write> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/**
+Writes an XDF file incrementally, for example during a live recording.
+
+The file header is written on creation. Stream headers, samples and clock offsets are written as soon as they are added,
+and a boundary chunk is written whenever [`DEFAULT_BOUNDARY_INTERVAL`] bytes (or the interval set with
+[`XDFWriter::with_boundary_interval`]) have passed since the last one.
+The stream footers are only written by [`XDFWriter::finish`], so make sure to call it once the recording is done.
+
+# Example
+```rust
+# use xdf::{Sample, Values, XDFFile, XDFWriter};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let header = xmltree::Element::parse(
+    "<info>
+        <name>SendData</name>
+        <type>EEG</type>
+        <channel_count>2</channel_count>
+        <nominal_srate>100</nominal_srate>
+        <channel_format>float32</channel_format>
+    </info>"
+        .as_bytes(),
+)?;
+
+let mut writer = XDFWriter::new(Vec::new())?;
+let eeg = writer.add_stream(&header)?;
+
+let samples = [
+    Sample { timestamp: Some(5.0), values: Values::Float32(vec![0.5, 1.5]) },
+    Sample { timestamp: None, values: Values::Float32(vec![0.6, 1.6]) },
+];
+writer.push_samples(eeg, &samples)?;
+writer.push_clock_offset(eeg, 5.5, -0.1)?;
+
+let bytes = writer.finish()?;
+let xdf_file = XDFFile::from_bytes(&bytes)?;
+# Ok(())
+# }
+```
+*/
+pub struct XDFWriter<W: Write> {
+    writer: CountingWriter<W>,
+    streams: Vec<StreamState>,
+    boundary_interval: u64,
+    last_boundary: u64,
+}
+
+impl<W: Write> XDFWriter<W> {
+    /**
+    Create a new writer and write the magic number and a file header for XDF version 1.0.
+    # Errors
+    Will error if writing fails.
+    */
+    pub fn new(writer: W) -> Result<Self, XDFError> {
+        Self::with_header(writer, &FileHeader::new(1.0).to_xml())
+    }
+
+    /**
+    Create a new writer and write the magic number and the given file header, for example from [`crate::FileHeader::to_xml`].
+    # Errors
+    Will error if writing fails.
+    */
+    pub fn with_header(writer: W, header: &Element) -> Result<Self, XDFError> {
+        let mut writer = CountingWriter {
+            inner: writer,
+            count: 0,
+        };
+        writer.write_all(b"XDF:")?;
+        writers::file_header(&mut writer, header)?;
+
+        Ok(Self {
+            last_boundary: writer.count,
+            writer,
+            streams: Vec::new(),
+            boundary_interval: DEFAULT_BOUNDARY_INTERVAL,
+        })
+    }
+
+    /// Set the number of bytes after which a boundary chunk is written.
+    #[must_use]
+    pub const fn with_boundary_interval(mut self, bytes: u64) -> Self {
+        self.boundary_interval = bytes;
+        self
+    }
+
+    /**
+    Add a stream and write its header. Stream IDs are assigned in order, starting at 1.
+    # Arguments
+    * `header` - The XML header of the stream, for example [`crate::Stream::header`].
+      It must contain at least `channel_count`, `nominal_srate` and `channel_format`.
+    # Errors
+    Will error if the header is invalid or writing fails.
+    */
+    pub fn add_stream(&mut self, header: &Element) -> Result<StreamHandle, XDFError> {
+        let info = stream_header_info(header).ok_or(WriteError::InvalidStreamHeader)?;
+
+        let stream_id = u32::try_from(self.streams.len() + 1).map_err(|_| WriteError::TooManyStreams)?;
+        writers::stream_header(&mut self.writer, stream_id, header)?;
+
+        self.streams.push(StreamState {
+            stream_id,
+            channel_count: info.channel_count,
+            format: info.channel_format,
+            nominal_srate: info.nominal_srate,
+            footer_stats: FooterStats::default(),
+        });
+
+        Ok(StreamHandle { stream_id })
+    }
+
+    /**
+    Write the samples to the stream as a single samples chunk.
+    # Errors
+    Will error if the stream does not belong to this writer, if a sample does not match the stream's format or channel count, or if writing fails.
+    */
+    pub fn push_samples(&mut self, stream: StreamHandle, samples: &[Sample]) -> Result<(), XDFError> {
+        let state = self
+            .streams
+            .iter_mut()
+            .find(|s| s.stream_id == stream.stream_id)
+            .ok_or(WriteError::UnknownStream(stream.stream_id))?;
+
+        writers::samples(
+            &mut self.writer,
+            state.stream_id,
+            state.channel_count,
+            state.format,
+            samples.iter().map(|s| (s.timestamp, &s.values)),
+        )?;
+        state.footer_stats.add_samples(samples);
+
+        self.write_boundary_if_due()
+    }
+
+    /**
+    Write a clock offset for the stream. Both values are in seconds.
+    # Errors
+    Will error if the stream does not belong to this writer or if writing fails.
+    */
+    pub fn push_clock_offset(
+        &mut self,
+        stream: StreamHandle,
+        collection_time: f64,
+        offset_value: f64,
+    ) -> Result<(), XDFError> {
+        let state = self
+            .streams
+            .iter_mut()
+            .find(|s| s.stream_id == stream.stream_id)
+            .ok_or(WriteError::UnknownStream(stream.stream_id))?;
+
+        let chunk = ClockOffsetChunk {
+            stream_id: state.stream_id,
+            collection_time,
+            offset_value,
+        };
+        writers::clock_offset(&mut self.writer, &chunk)?;
+        state.footer_stats.add_clock_offset(collection_time, offset_value);
+
+        self.write_boundary_if_due()
+    }
+
+    /**
+    Write the stream footers with the first and last timestamp, the sample count and the clock offsets of every stream, then flush.
+    # Returns
+    * The inner writer
+    # Errors
+    Will error if writing fails.
+    */
+    pub fn finish(mut self) -> Result<W, XDFError> {
+        for state in &self.streams {
+            let footer = state.footer_stats.footer(state.nominal_srate);
+            writers::stream_footer(&mut self.writer, state.stream_id, &footer)?;
+        }
+
+        self.writer.flush()?;
+        Ok(self.writer.inner)
+    }
+
+    fn write_boundary_if_due(&mut self) -> Result<(), XDFError> {
+        if self.writer.count - self.last_boundary >= self.boundary_interval {
+            writers::boundary(&mut self.writer)?;
+            self.last_boundary = self.writer.count;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Values, XDFFile};
+
+    #[test]
+    #[allow(clippy::float_cmp)] // the timestamps are written and read back unchanged
+    fn test_writer_footer() {
+        let header = Element::parse(
+            "<info><channel_count>1</channel_count><nominal_srate>10</nominal_srate><channel_format>int8</channel_format></info>"
+                .as_bytes(),
+        )
+        .unwrap();
+
+        let mut writer = XDFWriter::new(Vec::new()).unwrap().with_boundary_interval(0);
+        let stream = writer.add_stream(&header).unwrap();
+
+        let samples = [
+            Sample {
+                timestamp: Some(1.0),
+                values: Values::Int8(vec![1]),
+            },
+            Sample {
+                timestamp: None,
+                values: Values::Int8(vec![2]),
+            },
+        ];
+        writer.push_samples(stream, &samples).unwrap();
+        writer.push_samples(stream, &samples[1..]).unwrap();
+        writer.push_clock_offset(stream, 1.0, 0.5).unwrap();
+
+        let bytes = writer.finish().unwrap();
+        let xdf_file = XDFFile::from_bytes(&bytes).unwrap();
+
+        let footer = xdf_file.streams[0].footer.as_ref().unwrap();
+        let text = |name: &str| footer.get_child(name).unwrap().get_text().unwrap().to_string();
+        assert_eq!(text("first_timestamp"), "1");
+        assert_eq!(text("last_timestamp"), "1.2");
+        assert_eq!(text("sample_count"), "3");
+        assert_eq!(footer.get_child("clock_offsets").unwrap().children.len(), 1);
+
+        // interpolated using the nominal srate and corrected by the clock offset
+        for (sample, expected) in xdf_file.streams[0].samples.iter().zip([1.5, 1.6, 1.7]) {
+            assert!((sample.timestamp.unwrap() - expected).abs() < 1E-14);
+        }
+    }
+
+    #[test]
+    fn test_writer_rejects_mismatched_samples() {
+        let header = Element::parse(
+            "<info><channel_count>2</channel_count><nominal_srate>0</nominal_srate><channel_format>int8</channel_format></info>"
+                .as_bytes(),
+        )
+        .unwrap();
+
+        let mut writer = XDFWriter::new(Vec::new()).unwrap();
+        let stream = writer.add_stream(&header).unwrap();
+
+        let wrong_count = Sample {
+            timestamp: None,
+            values: Values::Int8(vec![1]),
+        };
+        let wrong_format = Sample {
+            timestamp: None,
+            values: Values::Int16(vec![1, 2]),
+        };
+        assert!(writer.push_samples(stream, &[wrong_count]).is_err());
+        assert!(writer.push_samples(stream, &[wrong_format]).is_err());
+    }
+}
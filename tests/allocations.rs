@@ -0,0 +1,49 @@
+// counts every allocation, so this has to be its own test binary
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fs,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use xdf::{ChunkError, ParseError, ParseErrorReason, XDFError, XDFFile};
+
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[test]
+fn parse_error_allocates_a_bounded_amount() {
+    // break the timestamp of the first sample near the start of a large file
+    let mut bytes = fs::read("tests/minimal.xdf").unwrap();
+    bytes[638] = 5;
+    bytes.resize(64 << 20, 0);
+
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let result = XDFFile::from_bytes(&bytes);
+    let allocated = ALLOCATED.load(Ordering::Relaxed) - before;
+
+    let Err(XDFError::Parse(ParseError::Chunk(e))) = result else {
+        panic!("expected a chunk error");
+    };
+    assert_eq!(e.reason, ParseErrorReason::InvalidTimestampBytes(5));
+    assert_eq!(e.offset, 638);
+    assert!(e.excerpt.len() <= ChunkError::EXCERPT_LEN);
+    assert_eq!(e.excerpt[0], 5);
+
+    // nothing close to the size of the input is copied
+    assert!(allocated < 1 << 20, "allocated {allocated} bytes");
+}
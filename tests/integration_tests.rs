@@ -1,9 +1,51 @@
-use std::fs;
+use std::{fs, ops::Range};
 
-use xdf::{Format, Values, XDFFile};
+use xdf::{
+    ChunkKind, ClockSync, DateTime, Diagnostic, DiagnosticKind, FileHeader, Format, Limit, ParseError,
+    ParseErrorReason, ParseLimits, ParseOptions, Sample, Segmentation, Severity, StreamError, UnknownChunk, Values,
+    WriteError, XDFError, XDFFile, XDFWriter,
+};
 
 const EPSILON: f64 = 1E-15;
 
+// a sample of a stream with a single int8 channel
+fn sample(timestamp: Option<f64>, value: i8) -> Sample {
+    Sample {
+        timestamp,
+        values: Values::Int8(vec![value]),
+    }
+}
+
+// writes a file with a single int8 stream with one channel, its samples chunks and then its clock offsets
+fn write_stream(srate: f64, chunks: &[&[Sample]], offsets: &[(f64, f64)]) -> Vec<u8> {
+    write_stream_with_header(&FileHeader::new(1.0), srate, chunks, offsets)
+}
+
+fn write_stream_with_header(
+    file_header: &FileHeader,
+    srate: f64,
+    chunks: &[&[Sample]],
+    offsets: &[(f64, f64)],
+) -> Vec<u8> {
+    let header = xmltree::Element::parse(
+        format!(
+            "<info><channel_count>1</channel_count><nominal_srate>{srate}</nominal_srate><channel_format>int8</channel_format></info>"
+        )
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let mut writer = XDFWriter::with_header(Vec::new(), &file_header.to_xml()).unwrap();
+    let stream = writer.add_stream(&header).unwrap();
+    for samples in chunks {
+        writer.push_samples(stream, samples).unwrap();
+    }
+    for &(collection_time, offset_value) in offsets {
+        writer.push_clock_offset(stream, collection_time, offset_value).unwrap();
+    }
+    writer.finish().unwrap()
+}
+
 #[allow(clippy::too_many_lines)]
 #[test]
 fn read_minimal_xdf() {
@@ -11,20 +53,19 @@ fn read_minimal_xdf() {
     let bytes = fs::read(file_path).unwrap();
     let xdf_file = XDFFile::from_bytes(&bytes).unwrap();
 
-    //must be sorted
-    let stream_ids: [u32; 2] = [0, 0x02C0_FFEE];
+    assert_eq!(xdf_file.header.version.to_bits(), 1.0_f32.to_bits());
+    #[allow(deprecated)]
+    let version = xdf_file.version;
+    assert_eq!(version.to_bits(), 1.0_f32.to_bits());
+    assert_eq!(xdf_file.header.datetime, None);
+    assert!(xdf_file.header.extra.is_empty());
 
-    assert_eq!(xdf_file.header.name, "info");
+    // the streams are in the order of their headers in the file
+    let ids_and_offsets: Vec<(u32, u64)> = xdf_file.streams.iter().map(|s| (s.id, s.header_offset)).collect();
+    assert_eq!(ids_and_offsets, [(0, 64), (0x02C0_FFEE, 327)]);
 
-    assert_eq!(xdf_file.streams.len(), stream_ids.len());
-    let mut read_ids = xdf_file.streams.iter().map(|stream| stream.id).collect::<Vec<u32>>();
-    let mut expected_ids = stream_ids;
-    read_ids.sort_unstable();
-    expected_ids.sort_unstable();
-    assert_eq!(read_ids, expected_ids);
-
-    let first_stream = xdf_file.streams.iter().find(|s| s.id == expected_ids[0]).unwrap();
-    let second_stream = xdf_file.streams.iter().find(|s| s.id == expected_ids[1]).unwrap();
+    let first_stream = &xdf_file.streams[0];
+    let second_stream = &xdf_file.streams[1];
 
     // test first stream
     // timestamps minus the clock offsets (always -0.1 in this file)
@@ -196,6 +237,566 @@ fn read_minimal_xdf() {
     }
 }
 
+#[test]
+fn round_trip_minimal_xdf() {
+    let bytes = fs::read("tests/minimal.xdf").unwrap();
+    let xdf_file = XDFFile::from_bytes(&bytes).unwrap();
+
+    let written = xdf_file.to_bytes().unwrap();
+    let mut read_back = XDFFile::from_bytes(&written).unwrap();
+
+    // the streams keep their order, but the file header is written differently, which moves the stream headers
+    for (stream, written_stream) in xdf_file.streams.iter().zip(&mut read_back.streams) {
+        assert!(written_stream.header_offset > stream.header_offset);
+        written_stream.header_offset = stream.header_offset;
+    }
+
+    assert_eq!(xdf_file, read_back);
+    assert_eq!(read_back.to_bytes().unwrap(), written);
+}
+
+#[test]
+fn write_samples_without_raw_timestamps() {
+    let bytes = fs::read("tests/minimal.xdf").unwrap();
+    let mut xdf_file = XDFFile::from_bytes(&bytes).unwrap();
+
+    let stream = &mut xdf_file.streams[0];
+    let sample = stream.samples[0].clone();
+    stream.samples.push(sample);
+    assert!(matches!(
+        xdf_file.to_bytes(),
+        Err(XDFError::Write(WriteError::TimestampCountMismatch {
+            samples: 10,
+            raw_timestamps: 9,
+            ..
+        }))
+    ));
+
+    let stream = &mut xdf_file.streams[0];
+    stream.raw_timestamps.push(Some(9.0));
+    let read_back = XDFFile::from_bytes(&xdf_file.to_bytes().unwrap()).unwrap();
+    assert_eq!(read_back.streams[0].samples.len(), 10);
+}
+
+#[test]
+fn read_minimal_xdf_from_reader() {
+    let bytes = fs::read("tests/minimal.xdf").unwrap();
+    let from_bytes = XDFFile::from_bytes(&bytes).unwrap();
+
+    let file = fs::File::open("tests/minimal.xdf").unwrap();
+    let from_reader = XDFFile::from_reader(file).unwrap();
+
+    assert_eq!(from_bytes, from_reader);
+}
+
+#[test]
+fn io_error_from_reader() {
+    struct FailingReader;
+    impl std::io::Read for FailingReader {
+        fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("read failed"))
+        }
+    }
+
+    let result = XDFFile::from_reader(FailingReader);
+    assert!(matches!(result, Err(xdf::XDFError::IO(_))));
+}
+
+#[test]
+fn probe_minimal_xdf() {
+    let bytes = fs::read("tests/minimal.xdf").unwrap();
+    let xdf_file = XDFFile::from_bytes(&bytes).unwrap();
+    let summary = XDFFile::probe(&bytes).unwrap();
+
+    assert_eq!(summary.header, xdf_file.header);
+
+    // the summary lists the streams in file order
+    let ids: Vec<u32> = summary.streams.iter().map(|s| s.id).collect();
+    assert_eq!(ids, [0, 0x02C0_FFEE]);
+
+    for (stream_summary, stream) in summary.streams.iter().zip(&xdf_file.streams) {
+        assert_eq!(stream_summary.id, stream.id);
+        assert_eq!(stream_summary.name, stream.name);
+        assert_eq!(stream_summary.r#type, stream.r#type);
+        assert_eq!(stream_summary.format, stream.format);
+        assert_eq!(stream_summary.channel_count, stream.channel_count);
+        assert_eq!(stream_summary.nominal_srate, stream.nominal_srate);
+        assert_eq!(stream_summary.header, stream.header);
+        assert_eq!(stream_summary.footer, stream.footer);
+        assert_eq!(stream_summary.footer_info, stream.footer_info);
+    }
+
+    // the limits apply to the chunks which are not skipped
+    let limits = ParseLimits {
+        max_channels: 2,
+        ..ParseLimits::default()
+    };
+    let result = XDFFile::probe_with(&bytes, &ParseOptions::new().limits(limits));
+    assert!(matches!(result, Err(XDFError::Parse(ParseError::Chunk(_)))));
+    let file = fs::File::open("tests/minimal.xdf").unwrap();
+    assert!(XDFFile::probe_reader_with(file, &ParseOptions::new().limits(limits)).is_err());
+}
+
+#[test]
+fn select_streams() {
+    let bytes = fs::read("tests/minimal.xdf").unwrap();
+    let all_streams = XDFFile::from_bytes(&bytes).unwrap();
+
+    let options =
+        ParseOptions::new().select_streams(|header| header.info.stream_type.as_deref() == Some("StringMarker"));
+    let selected = XDFFile::from_bytes_with(&bytes, &options).unwrap();
+
+    assert_eq!(selected.streams.len(), 1);
+    assert_eq!(selected.streams[0], all_streams.streams[1]);
+
+    let options = ParseOptions::new().select_streams(|header| header.stream_id == 7);
+    let none_selected = XDFFile::from_bytes_with(&bytes, &options).unwrap();
+    assert!(none_selected.streams.is_empty());
+}
+
+#[test]
+fn recover_from_corrupted_chunk() {
+    // corrupt the tag of the first samples chunk, which lies between two boundary chunks.
+    // Other unknown tags would be skipped, but no chunk has tag 0
+    let mut bytes = fs::read("tests/minimal.xdf").unwrap();
+    bytes[627] = 0x00;
+
+    // without recovery, parsing stops at the broken chunk, so the footers are lost
+    let xdf_file = XDFFile::from_bytes(&bytes).unwrap();
+    assert!(xdf_file.streams.iter().all(|s| s.footer.is_none()));
+    assert!(xdf_file.skipped_ranges.is_empty());
+
+    let xdf_file = XDFFile::from_bytes_with(&bytes, &ParseOptions::new().recover(true)).unwrap();
+
+    // everything up to the end of the next boundary chunk is skipped, including all samples
+    assert_eq!(xdf_file.skipped_ranges, vec![Range { start: 625, end: 1238 }]);
+    assert_eq!(xdf_file.streams.len(), 2);
+    assert!(xdf_file.streams.iter().all(|s| s.footer.is_some()));
+    assert!(xdf_file.streams.iter().all(|s| s.samples.is_empty()));
+}
+
+#[test]
+fn lenient_truncated_file() {
+    // cut the file in the middle of a samples chunk of the second stream, before the clock offsets and footers
+    let bytes = fs::read("tests/minimal.xdf").unwrap();
+    let bytes = &bytes[..1100];
+
+    let xdf_file = XDFFile::from_bytes(bytes).unwrap();
+    let truncation = xdf_file.truncation.unwrap();
+    assert_eq!(truncation.offset, 1061);
+    assert_eq!(truncation.recovered_samples, 0);
+    assert!(truncation.synthesized_footers.is_empty());
+    assert_eq!(xdf_file.streams[1].samples.len(), 1);
+    assert!(xdf_file.streams.iter().all(|s| s.footer.is_none()));
+
+    let xdf_file = XDFFile::from_bytes_with(bytes, &ParseOptions::new().lenient(true)).unwrap();
+    let truncation = xdf_file.truncation.unwrap();
+    assert_eq!(truncation.declared_samples, Some(4));
+    assert_eq!(truncation.recovered_samples, 2);
+    assert_eq!(truncation.synthesized_footers, vec![0, 0x02C0_FFEE]);
+
+    // the two complete samples of the cut-off chunk are kept
+    assert_eq!(xdf_file.streams[1].samples.len(), 3);
+    for stream in &xdf_file.streams {
+        let footer = stream.footer.as_ref().unwrap();
+        let sample_count = footer.get_child("sample_count").unwrap().get_text().unwrap();
+        assert_eq!(sample_count, stream.samples.len().to_string());
+    }
+}
+
+#[test]
+fn diagnostics() {
+    let bytes = fs::read("tests/minimal.xdf").unwrap();
+    let kinds = |diagnostics: &[Diagnostic]| -> Vec<(DiagnosticKind, Option<u32>, Option<u64>)> {
+        diagnostics.iter().map(|d| (d.kind, d.stream_id, d.offset)).collect()
+    };
+
+    // the footers list clock offsets which are not in the file
+    assert_eq!(
+        kinds(&XDFFile::from_bytes(&bytes).unwrap().diagnostics),
+        [
+            (DiagnosticKind::FooterClockOffsetMissing, Some(0), None),
+            (DiagnosticKind::FooterClockOffsetMissing, Some(0x02C0_FFEE), None),
+        ]
+    );
+
+    // the file is cut off before the footers
+    let truncated = &bytes[..1100];
+
+    let xdf_file = XDFFile::from_bytes(truncated).unwrap();
+    assert_eq!(
+        kinds(&xdf_file.diagnostics),
+        [
+            (DiagnosticKind::TruncatedChunk, Some(0x02C0_FFEE), Some(1061)),
+            (DiagnosticKind::MissingFooter, Some(0), Some(64)),
+            (DiagnosticKind::MissingFooter, Some(0x02C0_FFEE), Some(327)),
+        ]
+    );
+    assert_eq!(xdf_file.diagnostics[0].severity, Severity::Error);
+    assert_eq!(xdf_file.diagnostics[1].severity, Severity::Warning);
+
+    let xdf_file = XDFFile::from_bytes_with(truncated, &ParseOptions::new().lenient(true)).unwrap();
+    assert_eq!(
+        kinds(&xdf_file.diagnostics),
+        [
+            (DiagnosticKind::TruncatedChunk, Some(0x02C0_FFEE), Some(1061)),
+            (DiagnosticKind::MissingFooter, Some(0), Some(64)),
+            (DiagnosticKind::MissingFooter, Some(0x02C0_FFEE), Some(327)),
+            (DiagnosticKind::SynthesizedFooter, Some(0), None),
+            (DiagnosticKind::SynthesizedFooter, Some(0x02C0_FFEE), None),
+        ]
+    );
+
+    let summary = XDFFile::probe(truncated).unwrap();
+    assert_eq!(summary.diagnostics[0].kind, DiagnosticKind::TruncatedChunk);
+
+    // a broken chunk stops parsing unless recovering from it
+    let mut corrupted = bytes.clone();
+    corrupted[627] = 0x00;
+    let xdf_file = XDFFile::from_bytes(&corrupted).unwrap();
+    assert_eq!(xdf_file.diagnostics[0].kind, DiagnosticKind::UnparsedBytes);
+    assert_eq!(xdf_file.diagnostics[0].offset, Some(625));
+
+    // the samples up to the next boundary are skipped, so they are missing from the footers' sample counts
+    let xdf_file = XDFFile::from_bytes_with(&corrupted, &ParseOptions::new().recover(true)).unwrap();
+    assert_eq!(
+        kinds(&xdf_file.diagnostics)[..2],
+        [
+            (DiagnosticKind::SkippedBytes, None, Some(625)),
+            (DiagnosticKind::FooterSampleCountMismatch, Some(0), None)
+        ]
+    );
+}
+
+#[test]
+fn generated_channels() {
+    let bytes = fs::read("tests/minimal.xdf").unwrap();
+    let xdf_file = XDFFile::from_bytes(&bytes).unwrap();
+    let stream = &xdf_file.streams[0];
+
+    // the header has an empty desc
+    let labels: Vec<String> = stream.channels().into_iter().map(|c| c.label).collect();
+    assert_eq!(labels, ["Ch1", "Ch2", "Ch3"]);
+}
+
+#[test]
+fn footer_info() {
+    let bytes = fs::read("tests/minimal.xdf").unwrap();
+    let xdf_file = XDFFile::from_bytes(&bytes).unwrap();
+    let footer = xdf_file.streams[0].footer_info.as_ref().unwrap();
+
+    assert_eq!(footer.first_timestamp, Some(5.1));
+    assert_eq!(footer.last_timestamp, Some(5.9));
+    assert_eq!(footer.sample_count, Some(9));
+    assert_eq!(footer.clock_offsets, [(50979.76, -0.01), (50979.86, -0.02)]);
+
+    // the first footer belongs to stream 0 and claims one sample less and a later last timestamp
+    let footer_start = 1286;
+    let replace = |bytes: &mut [u8], from: &str, to: &str| {
+        let position = bytes[footer_start..]
+            .windows(from.len())
+            .position(|w| w == from.as_bytes())
+            .unwrap();
+        bytes[footer_start + position..][..to.len()].copy_from_slice(to.as_bytes());
+    };
+    let mut changed = bytes.clone();
+    replace(&mut changed, "<sample_count>9<", "<sample_count>8<");
+    replace(&mut changed, "<last_timestamp>5.9<", "<last_timestamp>6.9<");
+
+    let xdf_file = XDFFile::from_bytes(&changed).unwrap();
+    let kinds: Vec<(DiagnosticKind, Option<u32>)> =
+        xdf_file.diagnostics.iter().map(|d| (d.kind, d.stream_id)).collect();
+    assert_eq!(
+        kinds,
+        [
+            (DiagnosticKind::FooterSampleCountMismatch, Some(0)),
+            (DiagnosticKind::FooterTimestampMismatch, Some(0)),
+            (DiagnosticKind::FooterClockOffsetMissing, Some(0)),
+            (DiagnosticKind::FooterClockOffsetMissing, Some(0x02C0_FFEE)),
+        ]
+    );
+}
+
+#[test]
+fn non_finite_clock_offset_diagnostic() {
+    let bytes = write_stream(0.0, &[], &[(1.0, f64::NAN)]);
+
+    let xdf_file = XDFFile::from_bytes(&bytes).unwrap();
+    assert_eq!(xdf_file.diagnostics.len(), 1);
+    assert_eq!(xdf_file.diagnostics[0].kind, DiagnosticKind::NonFiniteClockOffset);
+    assert_eq!(xdf_file.diagnostics[0].stream_id, Some(1));
+}
+
+#[test]
+fn processing_switches() {
+    // two chunks which are out of order, each with a sample without a timestamp
+    let bytes = write_stream(
+        10.0,
+        &[
+            &[sample(Some(2.0), 1), sample(None, 2)],
+            &[sample(Some(1.0), 1), sample(None, 2)],
+        ],
+        &[(0.0, 0.5), (10.0, 0.5)],
+    );
+
+    let timestamps = |options: &ParseOptions| -> Vec<Option<f64>> {
+        let xdf_file = XDFFile::from_bytes_with(&bytes, options).unwrap();
+        xdf_file.streams[0].samples.iter().map(|s| s.timestamp).collect()
+    };
+    let assert_timestamps = |actual: Vec<Option<f64>>, expected: [Option<f64>; 4]| {
+        for (a, e) in actual.into_iter().zip(expected) {
+            match (a, e) {
+                (Some(a), Some(e)) => assert!((a - e).abs() < 1E-9, "{a} != {e}"),
+                (a, e) => assert_eq!(a, e),
+            }
+        }
+    };
+
+    assert_timestamps(
+        timestamps(&ParseOptions::new()),
+        [Some(1.5), Some(1.6), Some(2.5), Some(2.6)],
+    );
+    assert_timestamps(
+        timestamps(&ParseOptions::new().clock_correction(false)),
+        [Some(1.0), Some(1.1), Some(2.0), Some(2.1)],
+    );
+    assert_timestamps(
+        timestamps(&ParseOptions::new().interpolate_timestamps(false)),
+        [Some(1.5), None, Some(2.5), None],
+    );
+    assert_timestamps(
+        timestamps(&ParseOptions::new().sort_chunks(false)),
+        [Some(2.5), Some(2.6), Some(1.5), Some(1.6)],
+    );
+
+    let raw = ParseOptions::new()
+        .clock_correction(false)
+        .interpolate_timestamps(false)
+        .sort_chunks(false);
+    assert_timestamps(timestamps(&raw), [Some(2.0), None, Some(1.0), None]);
+}
+
+#[test]
+fn robust_clock_sync() {
+    // the clock drifts by 10 ppm, and one measurement right next to the sample is off by 50 ms
+    let offsets: Vec<(f64, f64)> = (0..20)
+        .map(|i| {
+            let collection_time = f64::from(i) * 5.0;
+            let outlier = if i == 10 { 0.05 } else { 0.0 };
+            (collection_time, 0.001 + 1E-5 * collection_time + outlier)
+        })
+        .collect();
+    let bytes = write_stream(0.0, &[&[sample(Some(50.0), 1)]], &offsets);
+
+    let timestamp = |clock_sync: ClockSync| {
+        let options = ParseOptions::new().clock_sync(clock_sync);
+        let xdf_file = XDFFile::from_bytes_with(&bytes, &options).unwrap();
+        xdf_file.streams[0].samples[0].timestamp.unwrap()
+    };
+
+    let expected = 50.0 + 0.001 + 1E-5 * 50.0;
+    assert!((timestamp(ClockSync::Interpolate) - (expected + 0.05)).abs() < 1E-9);
+    assert!((timestamp(ClockSync::LeastSquares) - expected).abs() > 1E-3);
+    assert!((timestamp(ClockSync::Robust { winsor_threshold: 1E-4 }) - expected).abs() < 1E-5);
+}
+
+#[test]
+fn dejitter() {
+    // two recordings of 100 samples each, 10 minutes apart, with every timestamp a little off
+    let samples: Vec<Sample> = (0..200)
+        .map(|i| {
+            let start = if i < 100 { 0.0 } else { 599.0 };
+            let jitter = if i % 2 == 0 { 0.002 } else { -0.002 };
+            sample(Some(start + f64::from(i) * 0.01 + jitter), 0)
+        })
+        .collect();
+    let bytes = write_stream(100.0, &[&samples], &[]);
+
+    let xdf_file = XDFFile::from_bytes_with(&bytes, &ParseOptions::new().dejitter(true)).unwrap();
+    let stream = &xdf_file.streams[0];
+
+    assert_eq!(stream.segments.len(), 2);
+    assert_eq!(stream.segments[0].range(), 0..100);
+    assert_eq!(stream.segments[1].range(), 100..200);
+    for segment in &stream.segments {
+        assert!((segment.effective_srate.unwrap() - 100.0).abs() < 0.1);
+        for pair in stream.samples[segment.range()].windows(2) {
+            let spacing = pair[1].timestamp.unwrap() - pair[0].timestamp.unwrap();
+            assert!((spacing - 1.0 / segment.effective_srate.unwrap()).abs() < 1E-9);
+        }
+    }
+    assert!((stream.effective_srate().unwrap() - 100.0).abs() < 0.1);
+
+    // the corrected timestamps keep the jitter
+    let timestamps: Vec<Option<f64>> = stream.samples.iter().map(|s| s.timestamp).collect();
+    assert_eq!(stream.corrected_timestamps, stream.interpolated_timestamps);
+    assert_ne!(stream.corrected_timestamps, timestamps);
+
+    // without dejittering, the segments are still found but the timestamps are kept
+    let xdf_file = XDFFile::from_bytes(&bytes).unwrap();
+    assert_eq!(xdf_file.streams[0].segments.len(), 2);
+    assert_eq!(xdf_file.streams[0].samples, samples);
+}
+
+#[test]
+fn clock_reset() {
+    let samples =
+        |start: f64| -> Vec<Sample> { (0..10).map(|i| sample(Some(start + f64::from(i) * 0.1), 0)).collect() };
+
+    // the sending computer restarts, so its clock starts over with a different offset
+    let bytes = write_stream(
+        10.0,
+        &[&samples(1050.0), &samples(50.0)],
+        &[(1000.0, 0.1), (1100.0, 0.1), (0.0, 3.0), (100.0, 3.0)],
+    );
+
+    // clock resets are only detected when asked for, otherwise the offsets are out of order
+    let result = XDFFile::from_bytes(&bytes);
+    assert!(matches!(result, Err(XDFError::Parse(ParseError::InvalidClockOffset))));
+
+    let with_resets = Segmentation {
+        detect_clock_resets: true,
+        ..Segmentation::default()
+    };
+    let xdf_file = XDFFile::from_bytes_with(&bytes, &ParseOptions::new().segmentation(with_resets)).unwrap();
+    let stream = &xdf_file.streams[0];
+
+    // the chunks are sorted by timestamp, so the samples recorded after the restart come first
+    assert_eq!(stream.segments.len(), 2);
+    assert_eq!(stream.segments[0].range(), 0..10);
+    assert!((stream.segments[0].first_timestamp - 53.0).abs() < 1E-9);
+    assert!((stream.segments[1].first_timestamp - 1050.1).abs() < 1E-9);
+}
+
+#[test]
+fn timestamp_columns() {
+    let bytes = fs::read("tests/minimal.xdf").unwrap();
+    let xdf_file = XDFFile::from_bytes(&bytes).unwrap();
+    let stream = &xdf_file.streams[0];
+
+    let expected_raw = [Some(5.1), Some(5.2), None, None, Some(5.5), Some(5.6), None, None, None];
+    assert_eq!(stream.raw_timestamps, expected_raw);
+
+    for (i, (interpolated, corrected)) in stream
+        .interpolated_timestamps
+        .iter()
+        .zip(&stream.corrected_timestamps)
+        .enumerate()
+    {
+        let interpolated = interpolated.unwrap();
+        let corrected = corrected.unwrap();
+        assert!((interpolated - (5.1 + i as f64 * 0.1)).abs() < 1E-9);
+        // the clock offsets of this stream are all -0.1
+        assert!((corrected - (interpolated - 0.1)).abs() < 1E-9);
+        assert_eq!(stream.samples[i].timestamp, Some(corrected));
+    }
+
+    assert_eq!(stream.clock_offsets.len(), 2);
+    assert!(stream.clock_offsets.iter().all(|&(_, offset)| offset == -0.1));
+    assert_eq!(stream.clock_offset_stats.drift_ppm, Some(0.0));
+    assert_eq!(stream.clock_offset_stats.rejected, 0);
+
+    // without the columns, the samples are the same and the file is written with their timestamps
+    let options = ParseOptions::new().timestamp_columns(false);
+    let without_columns = XDFFile::from_bytes_with(&bytes, &options).unwrap();
+    let stream_without_columns = &without_columns.streams[0];
+    assert!(stream_without_columns.raw_timestamps.is_empty());
+    assert!(stream_without_columns.interpolated_timestamps.is_empty());
+    assert!(stream_without_columns.corrected_timestamps.is_empty());
+    assert_eq!(stream_without_columns.samples, stream.samples);
+    assert_eq!(without_columns.diagnostics, xdf_file.diagnostics);
+
+    let read_back = XDFFile::from_bytes(&without_columns.to_bytes().unwrap()).unwrap();
+    assert!(read_back.streams[0].clock_offsets.is_empty());
+    assert_eq!(read_back.streams[0].samples, stream.samples);
+}
+
+#[test]
+fn keep_non_finite_clock_offsets() {
+    let bytes = write_stream(10.0, &[&[sample(Some(1.0), 1)]], &[(0.0, f64::NAN)]);
+
+    let xdf_file = XDFFile::from_bytes(&bytes).unwrap();
+    assert_eq!(xdf_file.streams[0].samples[0].timestamp, Some(1.0));
+    assert!(xdf_file.streams[0].clock_offsets.is_empty());
+    assert_eq!(xdf_file.streams[0].clock_offset_stats.rejected, 1);
+
+    // the offset is still reported, but now it is applied
+    let options = ParseOptions::new().drop_non_finite_clock_offsets(false);
+    let xdf_file = XDFFile::from_bytes_with(&bytes, &options).unwrap();
+    assert_eq!(xdf_file.diagnostics[0].kind, DiagnosticKind::NonFiniteClockOffset);
+    assert!(xdf_file.streams[0].samples[0].timestamp.unwrap().is_nan());
+    assert_eq!(xdf_file.streams[0].clock_offsets.len(), 1);
+    assert_eq!(xdf_file.streams[0].clock_offset_stats.rejected, 0);
+}
+
+#[test]
+fn recording_start() {
+    let session = xmltree::Element::parse("<session><subject>P01</subject></session>".as_bytes()).unwrap();
+    let header = FileHeader {
+        datetime: DateTime::parse("2019-06-26T14:13:46+0200"),
+        extra: vec![session],
+        ..FileHeader::new(1.0)
+    };
+
+    let samples = [sample(Some(100.0), 1), sample(Some(100.5), 1)];
+    let bytes = write_stream_with_header(&header, 10.0, &[&samples], &[]);
+
+    let xdf_file = XDFFile::from_bytes(&bytes).unwrap();
+    assert_eq!(xdf_file.header, header);
+    assert_eq!(xdf_file.unix_time(100.0), Some(1_561_551_226.0));
+    assert_eq!(xdf_file.unix_time(100.5), Some(1_561_551_226.5));
+
+    let minimal = XDFFile::from_bytes(&fs::read("tests/minimal.xdf").unwrap()).unwrap();
+    assert_eq!(minimal.unix_time(5.0), None);
+}
+
+#[test]
+fn parse_limits() {
+    let bytes = fs::read("tests/minimal.xdf").unwrap();
+
+    // a huge length is rejected before anything is allocated for it
+    let mut huge_chunk = bytes[..625].to_vec();
+    huge_chunk.push(8);
+    huge_chunk.extend_from_slice(&u64::MAX.to_le_bytes());
+
+    let limits = ParseLimits {
+        max_chunk_size: 1 << 20,
+        ..ParseLimits::default()
+    };
+    let options = ParseOptions::new().limits(limits);
+    assert!(XDFFile::from_bytes_with(&bytes, &options).is_ok());
+
+    let Err(XDFError::Parse(ParseError::Chunk(e))) = XDFFile::from_bytes_with(&huge_chunk, &options) else {
+        panic!("expected the chunk size limit to be exceeded");
+    };
+    assert_eq!(
+        e.reason,
+        ParseErrorReason::LimitExceeded {
+            limit: Limit::ChunkSize,
+            value: u64::MAX,
+            max: 1 << 20
+        }
+    );
+    assert_eq!(e.offset, 626);
+
+    // exceeding a limit is not recovered from
+    let limits = ParseLimits {
+        max_decoded_bytes: 100,
+        ..ParseLimits::default()
+    };
+    let options = ParseOptions::new().limits(limits).recover(true);
+    let Err(XDFError::Parse(ParseError::Chunk(e))) = XDFFile::from_bytes_with(&bytes, &options) else {
+        panic!("expected the decoded bytes limit to be exceeded");
+    };
+    assert!(matches!(
+        e.reason,
+        ParseErrorReason::LimitExceeded {
+            limit: Limit::DecodedBytes,
+            ..
+        }
+    ));
+}
+
 #[test]
 fn fail_on_invalid_xdf() {
     let invalid_bytes = "This is not a valid XDF file!".as_bytes();
@@ -203,6 +804,94 @@ fn fail_on_invalid_xdf() {
     assert!(xdf_file.is_err());
 }
 
+#[test]
+fn unsupported_version() {
+    let bytes = fs::read("tests/minimal.xdf").unwrap();
+    let version = b"<version>1.0</version>";
+    let start = bytes.windows(version.len()).position(|w| w == version).unwrap();
+    let mut version_2 = bytes.clone();
+    // the version keeps its length, so the length of the chunk stays valid
+    version_2[start..start + version.len()].copy_from_slice(b"<version>2.0</version>");
+
+    let result = XDFFile::from_bytes(&version_2);
+    assert!(
+        matches!(result, Err(XDFError::Stream(StreamError::UnsupportedVersion(v))) if v.to_bits() == 2.0_f32.to_bits())
+    );
+    assert!(XDFFile::probe(&version_2).is_err());
+    let options = ParseOptions::new().allow_unknown_versions(true);
+    assert_eq!(XDFFile::probe_with(&version_2, &options).unwrap().streams.len(), 2);
+
+    let xdf_file = XDFFile::from_bytes_with(&version_2, &ParseOptions::new().allow_unknown_versions(true)).unwrap();
+    assert_eq!(xdf_file.header.version.to_bits(), 2.0_f32.to_bits());
+    assert_eq!(xdf_file.diagnostics[0].kind, DiagnosticKind::UnsupportedVersion);
+    assert_eq!(xdf_file.diagnostics[0].offset, Some(4));
+    assert_eq!(xdf_file.streams.len(), 2);
+}
+
+#[test]
+fn multiple_file_headers() {
+    let bytes = fs::read("tests/minimal.xdf").unwrap();
+    // the file header chunk is followed by the first stream header at byte 64
+    let mut doubled = bytes[..64].to_vec();
+    doubled.extend_from_slice(&bytes[4..]);
+
+    let result = XDFFile::from_bytes(&doubled);
+    assert!(matches!(result, Err(XDFError::Stream(StreamError::MultipleFileHeader))));
+    assert!(matches!(
+        XDFFile::probe(&doubled),
+        Err(XDFError::Stream(StreamError::MultipleFileHeader))
+    ));
+
+    let xdf_file = XDFFile::from_bytes_with(&doubled, &ParseOptions::new().recover(true)).unwrap();
+    assert_eq!(xdf_file.diagnostics[0].kind, DiagnosticKind::MultipleFileHeader);
+    assert_eq!(xdf_file.diagnostics[0].offset, Some(64));
+    assert_eq!(xdf_file.streams.len(), 2);
+
+    // a file header must be the first chunk
+    let result = XDFFile::from_bytes(&[&bytes[..4], &bytes[64..]].concat());
+    let Err(XDFError::Stream(e @ StreamError::FileHeaderNotFirst { kind, offset })) = result else {
+        panic!("expected the file header to be missing");
+    };
+    assert_eq!(kind, Some(ChunkKind::StreamHeader));
+    assert_eq!(offset, 4);
+    assert_eq!(
+        e.to_string(),
+        "The file starts with a stream header chunk at byte offset 4 instead of a file header chunk"
+    );
+
+    let result = XDFFile::from_bytes(&bytes[..4]);
+    assert!(matches!(result, Err(XDFError::Stream(StreamError::MissingFileHeader))));
+}
+
+#[test]
+fn unknown_chunks() {
+    let bytes = fs::read("tests/minimal.xdf").unwrap();
+    // a chunk with tag 7 and three bytes of content in front of the first stream header at byte 64
+    let unknown_chunk = [1, 5, 7, 0, 0xAA, 0xBB, 0xCC];
+    let with_unknown = [&bytes[..64], &unknown_chunk, &bytes[64..]].concat();
+
+    let xdf_file = XDFFile::from_bytes(&with_unknown).unwrap();
+    assert_eq!(xdf_file.streams.len(), 2);
+    assert!(xdf_file.streams.iter().all(|s| s.footer.is_some()));
+    assert_eq!(
+        xdf_file.unknown_chunks,
+        [UnknownChunk {
+            tag: 7,
+            content: vec![0xAA, 0xBB, 0xCC]
+        }]
+    );
+    assert_eq!(xdf_file.diagnostics[0].kind, DiagnosticKind::UnknownChunk);
+    assert_eq!(xdf_file.diagnostics[0].offset, Some(64));
+
+    let summary = XDFFile::probe(&with_unknown).unwrap();
+    assert_eq!(summary.streams.len(), 2);
+    assert_eq!(summary.diagnostics[0].kind, DiagnosticKind::UnknownChunk);
+
+    // the chunk is written back
+    let written = XDFFile::from_bytes(&xdf_file.to_bytes().unwrap()).unwrap();
+    assert_eq!(written.unknown_chunks, xdf_file.unknown_chunks);
+}
+
 #[test]
 fn out_of_order_clock_offsets() {
     // this was discovered by fuzzing